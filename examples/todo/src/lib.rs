@@ -3,7 +3,7 @@
 
 use vdom::{
     driver::Driver,
-    vdom::node::{Comp, CompCtx, CompNode, IntoNode, Node},
+    vdom::node::{Comp, CompCtx, CompNode, Node},
 };
 use vdom_macro::html;
 use vdom_web::{driver::App, Error};
@@ -74,7 +74,7 @@ where
         });
 
         html! {
-            div test=(input.to_string()) {
+            div data-test=(input.to_string()) {
                 (kek)
             }
         }
@@ -108,7 +108,7 @@ where
         web::console::log_1(&err.into());
 
         html! {
-            (input.to_string().into_node())
+            "{input}"
         }
     }
 }
@@ -0,0 +1,257 @@
+#![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
+
+use futures::{
+    task::{Context, Poll, Waker},
+    Sink, Stream,
+};
+use std::{
+    cell::{Ref, RefCell},
+    convert::Infallible,
+    mem,
+    pin::Pin,
+    rc::{Rc, Weak},
+};
+
+struct Inner<T> {
+    value: T,
+    /// Bumped on every write, for streams to tell whether they've seen the latest value.
+    version: u64,
+    wakers: Vec<Waker>,
+}
+
+/// A single threaded cell, which can be written to as a `Sink` and observed as a `Stream`.
+///
+/// Clones share the same value, e.g. to hand one to every component depending on it.
+pub struct SinkCell<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> SinkCell<T> {
+    pub fn new(value: T) -> SinkCell<T> {
+        SinkCell {
+            inner: Rc::new(RefCell::new(Inner {
+                value,
+                version: 0,
+                wakers: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.inner.borrow(), |inner| &inner.value)
+    }
+
+    pub fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        f(&self.inner.borrow().value)
+    }
+
+    /// Replaces the value and notifies all the change streams, returning the old value.
+    pub fn set(&self, value: T) -> T {
+        self.update(|v| mem::replace(v, value))
+    }
+
+    /// Modifies the value in place and notifies all the change streams.
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        let (res, wakers) = {
+            let inner = &mut *self.inner.borrow_mut();
+            let res = f(&mut inner.value);
+            inner.version += 1;
+            (res, mem::take(&mut inner.wakers))
+        };
+        // woken after releasing the borrow, in case a waker polls right away
+        for waker in wakers {
+            waker.wake();
+        }
+        res
+    }
+
+    /// A stream yielding the value after every change.
+    ///
+    /// Changes made in between two polls are coalesced, only the latest value is yielded. The
+    /// stream ends when all the cells are dropped.
+    pub fn changes(&self) -> Changes<T> {
+        Changes {
+            inner: Rc::downgrade(&self.inner),
+            version: self.inner.borrow().version,
+        }
+    }
+}
+
+impl<T> SinkCell<T>
+where
+    T: Clone,
+{
+    pub fn get(&self) -> T {
+        self.inner.borrow().value.clone()
+    }
+}
+
+impl<T> Default for SinkCell<T>
+where
+    T: Default,
+{
+    fn default() -> SinkCell<T> {
+        SinkCell::new(T::default())
+    }
+}
+
+impl<T> Clone for SinkCell<T> {
+    fn clone(&self) -> Self {
+        SinkCell {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for SinkCell<T> {
+    fn drop(&mut self) {
+        // lets the change streams end, once the last cell is gone
+        if Rc::strong_count(&self.inner) == 1 {
+            let wakers = mem::take(&mut self.inner.borrow_mut().wakers);
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Cells are equal if they share the same value, which makes them usable in component inputs.
+impl<T> PartialEq for SinkCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Eq for SinkCell<T> {}
+
+/// Writing never blocks, every item sent replaces the value.
+impl<T> Sink<T> for SinkCell<T> {
+    type Error = Infallible;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Infallible> {
+        SinkCell::set(&*self, item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// The changes of a `SinkCell`, see `SinkCell::changes`.
+pub struct Changes<T> {
+    inner: Weak<RefCell<Inner<T>>>,
+    version: u64,
+}
+
+impl<T> Stream for Changes<T>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let inner = match self.inner.upgrade() {
+            Some(inner) => inner,
+            None => return Poll::Ready(None),
+        };
+        let inner = &mut *inner.borrow_mut();
+        if inner.version != self.version {
+            self.version = inner.version;
+            Poll::Ready(Some(inner.value.clone()))
+        } else {
+            if !inner.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                inner.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{
+        executor::block_on,
+        task::{waker, ArcWake},
+        SinkExt, StreamExt,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Counts how often it was woken.
+    #[derive(Default)]
+    struct WakeCounter(AtomicUsize);
+
+    impl ArcWake for WakeCounter {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn poll_changes<T>(changes: &mut Changes<T>, counter: &Arc<WakeCounter>) -> Poll<Option<T>>
+    where
+        T: Clone,
+    {
+        let waker = waker(counter.clone());
+        Pin::new(changes).poll_next(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn writes_through_the_sink() {
+        let cell = SinkCell::new(0);
+        let mut changes = cell.changes();
+        let mut sink = cell.clone();
+        block_on(sink.send(1)).unwrap();
+        block_on(sink.send(2)).unwrap();
+        assert_eq!(cell.get(), 2);
+        assert_eq!(block_on(changes.next()), Some(2));
+    }
+
+    #[test]
+    fn coalesces_changes_into_one_wakeup() {
+        let counter = Arc::new(WakeCounter::default());
+        let cell = SinkCell::new(0);
+        let mut changes = cell.changes();
+        assert_eq!(poll_changes(&mut changes, &counter), Poll::Pending);
+
+        cell.set(1);
+        cell.set(2);
+        cell.update(|v| *v += 1);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert_eq!(poll_changes(&mut changes, &counter), Poll::Ready(Some(3)));
+        assert_eq!(poll_changes(&mut changes, &counter), Poll::Pending);
+    }
+
+    #[test]
+    fn ends_the_stream_when_the_cell_is_dropped() {
+        let counter = Arc::new(WakeCounter::default());
+        let cell = SinkCell::new(0);
+        let clone = cell.clone();
+        let mut changes = cell.changes();
+        assert_eq!(poll_changes(&mut changes, &counter), Poll::Pending);
+
+        drop(cell);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+        assert_eq!(poll_changes(&mut changes, &counter), Poll::Pending);
+        drop(clone);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert_eq!(poll_changes(&mut changes, &counter), Poll::Ready(None));
+    }
+}
@@ -1,20 +1,45 @@
-use futures::Future;
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use futures::{
+    future::LocalFutureObj,
+    task::{LocalSpawn, SpawnError},
+};
+use std::{cell::RefCell, rc::Rc};
 
-pub trait Driver /*: LocalSpawn */ {
+/// Mounts and patches nodes.
+///
+/// Futures, e.g. of components and suspenses, are spawned through `LocalSpawn`, so any single
+/// threaded executor can be plugged in, like a `LocalPool` or a deterministic one for tests.
+pub trait Driver: LocalSpawn {
     type AttrStore;
     type TagStore;
     type TextStore;
+    type CommentStore;
     type CompStore;
+    type PortalStore;
+    type RawHtmlStore;
+
+    /// The element of a mounted tag, handed out through `NodeRef`s.
+    type Element: Clone + PartialEq;
+
+    /// The error of mounting and patching nodes.
+    type Err;
 
     fn new_attr_store() -> Self::AttrStore;
     fn new_tag_store() -> Self::TagStore;
     fn new_text_store() -> Self::TextStore;
+    fn new_comment_store() -> Self::CommentStore;
     fn new_comp_store() -> Self::CompStore;
+    fn new_portal_store() -> Self::PortalStore;
+    fn new_raw_html_store() -> Self::RawHtmlStore;
 
-    fn spawn<F>(&mut self, fut: F)
-    where
-        F: Future<Output = ()> + 'static;
+    /// Called when a mounted component requested a render, see `CompCtx::request_render`.
+    ///
+    /// The driver is expected to refresh the dirty components soon, e.g. from a spawned future,
+    /// coalescing multiple requests into one refresh.
+    fn request_render(&mut self);
+
+    /// Handles the errors of spawned futures, e.g. of refreshing components or mounting resolved
+    /// suspense, which have no caller to return them to. See `DriverCtx::report_error`.
+    fn error_handler(&self) -> Rc<dyn Fn(Self::Err)>;
 }
 
 struct DriverInstance<D> {
@@ -35,7 +60,7 @@ impl<D> DriverCtx<D> {
 
     pub fn next_id(&self) -> u64 {
         let mut instance = self.instance.borrow_mut();
-        assert!(instance.id < u64::max_value());
+        assert!(instance.id < u64::MAX);
         instance.id += 1;
         instance.id
     }
@@ -57,6 +82,18 @@ impl<D> DriverCtx<D> {
     }
 }
 
+impl<D> DriverCtx<D>
+where
+    D: Driver,
+{
+    /// Hands `err` to the error handler of the driver, which is called after releasing the
+    /// driver, so it may use the context again.
+    pub fn report_error(&self, err: D::Err) {
+        let error_handler = self.with(|drv| drv.error_handler());
+        error_handler(err);
+    }
+}
+
 impl<D> Clone for DriverCtx<D> {
     fn clone(&self) -> Self {
         DriverCtx {
@@ -64,3 +101,19 @@ impl<D> Clone for DriverCtx<D> {
         }
     }
 }
+
+/// Spawns on the driver, e.g. to hand the context to code taking any `LocalSpawn`.
+///
+/// Must not be used while the driver is borrowed, e.g. from within `DriverCtx::with_mut`.
+impl<D> LocalSpawn for DriverCtx<D>
+where
+    D: LocalSpawn,
+{
+    fn spawn_local_obj(&mut self, future: LocalFutureObj<'static, ()>) -> Result<(), SpawnError> {
+        self.with_mut(|drv| drv.spawn_local_obj(future))
+    }
+
+    fn status_local(&self) -> Result<(), SpawnError> {
+        self.with(|drv| drv.status_local())
+    }
+}
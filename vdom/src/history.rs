@@ -0,0 +1,229 @@
+use crate::{
+    driver::Driver,
+    program::{Cmd, Dispatcher, Program},
+};
+use std::collections::VecDeque;
+
+/// Records the models of a program, to undo, redo and travel to any recorded state.
+///
+/// Wraps the program, so every step is diffed and applied like any other update. The number of
+/// recorded states is bounded, the oldest ones are dropped first.
+pub struct History<P> {
+    program: P,
+    capacity: usize,
+}
+
+impl<P> History<P> {
+    /// Keeps up to `capacity` states, including the present one.
+    pub fn new(program: P, capacity: usize) -> History<P> {
+        assert!(capacity > 0, "capacity must be at least 1");
+        History { program, capacity }
+    }
+}
+
+pub enum HistoryMsg<Msg> {
+    /// A message of the wrapped program, recording the resulting state.
+    Msg(Msg),
+    Undo,
+    Redo,
+    /// Travels to the recorded state with that index, the oldest state being `0`.
+    Jump(usize),
+}
+
+/// The recorded states of a `History`.
+pub struct HistoryModel<M> {
+    states: VecDeque<M>,
+    current: usize,
+}
+
+impl<M> HistoryModel<M> {
+    pub fn present(&self) -> &M {
+        &self.states[self.current]
+    }
+
+    /// The recorded states, from the oldest to the latest.
+    pub fn states(&self) -> &VecDeque<M> {
+        &self.states
+    }
+
+    /// The index of the present state among the recorded ones.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.current + 1 < self.states.len()
+    }
+}
+
+impl<D, P> Program<D> for History<P>
+where
+    D: Driver,
+    P: Program<D>,
+    P::Model: Clone,
+{
+    type Model = HistoryModel<P::Model>;
+    type Msg = HistoryMsg<P::Msg>;
+    type View = P::View;
+
+    fn init(&self, dispatcher: &Dispatcher<Self::Msg>) -> (Self::Model, Cmd<Self::Msg>) {
+        let (model, cmd) = self.program.init(&dispatcher.map(HistoryMsg::Msg));
+        let mut states = VecDeque::with_capacity(self.capacity);
+        states.push_back(model);
+        (
+            HistoryModel { states, current: 0 },
+            cmd.map(HistoryMsg::Msg),
+        )
+    }
+
+    fn update(&self, model: &mut Self::Model, msg: Self::Msg) -> Cmd<Self::Msg> {
+        match msg {
+            HistoryMsg::Msg(msg) => {
+                let mut state = model.present().clone();
+                let cmd = self.program.update(&mut state, msg);
+                // a new state drops the undone ones
+                model.states.truncate(model.current + 1);
+                if model.states.len() == self.capacity {
+                    model.states.pop_front();
+                }
+                model.states.push_back(state);
+                model.current = model.states.len() - 1;
+                return cmd.map(HistoryMsg::Msg);
+            }
+            HistoryMsg::Undo if model.can_undo() => model.current -= 1,
+            HistoryMsg::Redo if model.can_redo() => model.current += 1,
+            HistoryMsg::Jump(index) if index < model.states.len() => model.current = index,
+            HistoryMsg::Undo | HistoryMsg::Redo | HistoryMsg::Jump(_) => {}
+        }
+        Cmd::none()
+    }
+
+    fn view(&self, model: &Self::Model, dispatcher: &Dispatcher<Self::Msg>) -> P::View {
+        self.program
+            .view(model.present(), &dispatcher.map(HistoryMsg::Msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::tests::{detached_dispatcher, TestDriver};
+
+    /// Adds the numbers it gets to its model.
+    struct Adder;
+
+    impl Program<TestDriver> for Adder {
+        type Model = i32;
+        type Msg = i32;
+        type View = ();
+
+        fn init(&self, _dispatcher: &Dispatcher<i32>) -> (i32, Cmd<i32>) {
+            (0, Cmd::none())
+        }
+
+        fn update(&self, model: &mut i32, msg: i32) -> Cmd<i32> {
+            *model += msg;
+            Cmd::none()
+        }
+
+        fn view(&self, _model: &i32, _dispatcher: &Dispatcher<i32>) {}
+    }
+
+    fn init(history: &History<Adder>) -> HistoryModel<i32> {
+        Program::<TestDriver>::init(history, &detached_dispatcher()).0
+    }
+
+    fn update(history: &History<Adder>, model: &mut HistoryModel<i32>, msgs: Vec<HistoryMsg<i32>>) {
+        for msg in msgs {
+            Program::<TestDriver>::update(history, model, msg);
+        }
+    }
+
+    fn states(model: &HistoryModel<i32>) -> Vec<i32> {
+        model.states().iter().cloned().collect()
+    }
+
+    #[test]
+    fn undoes_and_redoes() {
+        let history = History::new(Adder, 10);
+        let mut model = init(&history);
+        update(
+            &history,
+            &mut model,
+            vec![HistoryMsg::Msg(1), HistoryMsg::Msg(2)],
+        );
+        assert_eq!(*model.present(), 3);
+        assert!(!model.can_redo());
+
+        update(
+            &history,
+            &mut model,
+            vec![HistoryMsg::Undo, HistoryMsg::Undo],
+        );
+        assert_eq!(*model.present(), 0);
+        assert!(!model.can_undo());
+        update(
+            &history,
+            &mut model,
+            vec![HistoryMsg::Undo, HistoryMsg::Redo],
+        );
+        assert_eq!(*model.present(), 1);
+        assert_eq!(model.current(), 1);
+        assert_eq!(states(&model), [0, 1, 3]);
+    }
+
+    #[test]
+    fn jumps_to_recorded_states() {
+        let history = History::new(Adder, 10);
+        let mut model = init(&history);
+        update(
+            &history,
+            &mut model,
+            vec![HistoryMsg::Msg(1), HistoryMsg::Msg(2)],
+        );
+        update(&history, &mut model, vec![HistoryMsg::Jump(0)]);
+        assert_eq!(*model.present(), 0);
+        update(&history, &mut model, vec![HistoryMsg::Jump(2)]);
+        assert_eq!(*model.present(), 3);
+        update(&history, &mut model, vec![HistoryMsg::Jump(3)]);
+        assert_eq!(model.current(), 2);
+    }
+
+    #[test]
+    fn clears_the_redo_history_on_new_messages() {
+        let history = History::new(Adder, 10);
+        let mut model = init(&history);
+        update(
+            &history,
+            &mut model,
+            vec![HistoryMsg::Msg(1), HistoryMsg::Msg(2), HistoryMsg::Undo],
+        );
+        update(&history, &mut model, vec![HistoryMsg::Msg(10)]);
+        assert_eq!(states(&model), [0, 1, 11]);
+        assert!(!model.can_redo());
+    }
+
+    #[test]
+    fn evicts_the_oldest_states_at_capacity() {
+        let history = History::new(Adder, 3);
+        let mut model = init(&history);
+        update(
+            &history,
+            &mut model,
+            vec![HistoryMsg::Msg(1), HistoryMsg::Msg(1), HistoryMsg::Msg(1)],
+        );
+        assert_eq!(states(&model), [1, 2, 3]);
+        assert_eq!(model.current(), 2);
+        update(
+            &history,
+            &mut model,
+            vec![HistoryMsg::Undo, HistoryMsg::Undo],
+        );
+        assert_eq!(*model.present(), 1);
+        assert!(!model.can_undo());
+    }
+}
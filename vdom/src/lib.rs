@@ -1,4 +1,6 @@
 #![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
 
 pub mod driver;
+pub mod history;
+pub mod program;
 pub mod vdom;
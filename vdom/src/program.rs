@@ -0,0 +1,595 @@
+use crate::{
+    driver::{Driver, DriverCtx},
+    vdom::node::Node,
+};
+use futures::{
+    future,
+    task::{LocalSpawnExt, SpawnError},
+    Future, FutureExt,
+};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt::Debug,
+    pin::Pin,
+    rc::{Rc, Weak},
+};
+
+/// An application with a single model, changed by messages only, in the style of the Elm
+/// architecture.
+pub trait Program<D>: 'static
+where
+    D: Driver,
+{
+    type Model: 'static;
+    type Msg: 'static;
+    type View: Node<D>;
+
+    /// Creates the first model; `dispatcher` queues messages to be handled once the first view
+    /// is mounted.
+    fn init(&self, dispatcher: &Dispatcher<Self::Msg>) -> (Self::Model, Cmd<Self::Msg>);
+
+    fn update(&self, model: &mut Self::Model, msg: Self::Msg) -> Cmd<Self::Msg>;
+
+    /// Renders the model; `dispatcher` can be moved into the event handlers of the view.
+    fn view(&self, model: &Self::Model, dispatcher: &Dispatcher<Self::Msg>) -> Self::View;
+}
+
+/// Side effects returned from `Program::init` and `Program::update`.
+///
+/// The futures are spawned by the driver, the messages they resolve to are dispatched back to the
+/// program.
+pub struct Cmd<Msg> {
+    futures: Vec<Pin<Box<dyn Future<Output = Option<Msg>>>>>,
+}
+
+impl<Msg> Cmd<Msg>
+where
+    Msg: 'static,
+{
+    pub fn none() -> Cmd<Msg> {
+        Cmd {
+            futures: Vec::new(),
+        }
+    }
+
+    /// Dispatches the message `fut` resolves to.
+    pub fn new<F>(fut: F) -> Cmd<Msg>
+    where
+        F: Future<Output = Msg> + 'static,
+    {
+        Cmd {
+            futures: vec![Box::pin(fut.map(Some))],
+        }
+    }
+
+    /// Runs `fut` without dispatching anything.
+    pub fn spawn<F>(fut: F) -> Cmd<Msg>
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        Cmd {
+            futures: vec![Box::pin(fut.map(|_| None))],
+        }
+    }
+
+    pub fn batch<I>(cmds: I) -> Cmd<Msg>
+    where
+        I: IntoIterator<Item = Cmd<Msg>>,
+    {
+        Cmd {
+            futures: cmds.into_iter().flat_map(|cmd| cmd.futures).collect(),
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.futures.is_empty()
+    }
+
+    /// Wraps the messages, e.g. to embed the commands of another program.
+    pub fn map<F, U>(self, f: F) -> Cmd<U>
+    where
+        F: Fn(Msg) -> U + 'static,
+        U: 'static,
+    {
+        let f = Rc::new(f);
+        Cmd {
+            futures: self
+                .futures
+                .into_iter()
+                .map(|fut| {
+                    let f = f.clone();
+                    Box::pin(fut.map(move |msg| msg.map(|msg| f(msg))))
+                        as Pin<Box<dyn Future<Output = Option<U>>>>
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Where the views of a program are mounted, e.g. the app of a driver.
+pub trait ProgramRoot<D, N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    type Err: Debug;
+
+    fn driver_ctx(&self) -> DriverCtx<D>;
+
+    /// Diffs `node` against the mounted node and applies the changes.
+    fn set(&mut self, node: N) -> Result<(), Self::Err>;
+}
+
+struct MsgQueue<Msg> {
+    msgs: VecDeque<Msg>,
+    is_flush_spawned: bool,
+    /// Spawns a future updating the program with the queued messages.
+    spawn_flush: Option<Rc<dyn Fn() -> Result<(), SpawnError>>>,
+}
+
+/// Spawns a future handling the queued messages, unless there is one already or the program
+/// isn't started yet.
+fn schedule_flush<Msg>(queue: &RefCell<MsgQueue<Msg>>) -> Result<(), SpawnError> {
+    let spawn_flush = {
+        let queue = &mut *queue.borrow_mut();
+        if queue.is_flush_spawned {
+            return Ok(());
+        }
+        match queue.spawn_flush.clone() {
+            Some(spawn_flush) => {
+                queue.is_flush_spawned = true;
+                spawn_flush
+            }
+            None => return Ok(()),
+        }
+    };
+    if let Err(err) = spawn_flush() {
+        queue.borrow_mut().is_flush_spawned = false;
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Queues messages for a program, e.g. from event handlers.
+///
+/// All the messages dispatched before the program gets to run are handled in one batch, followed
+/// by a single render.
+pub struct Dispatcher<Msg> {
+    dispatch: Rc<dyn Fn(Msg) -> Result<(), SpawnError>>,
+}
+
+impl<Msg> Dispatcher<Msg>
+where
+    Msg: 'static,
+{
+    fn new(queue: Weak<RefCell<MsgQueue<Msg>>>) -> Dispatcher<Msg> {
+        Dispatcher {
+            dispatch: Rc::new(move |msg| {
+                let queue = match queue.upgrade() {
+                    Some(queue) => queue,
+                    None => return Ok(()),
+                };
+                queue.borrow_mut().msgs.push_back(msg);
+                schedule_flush(&queue)
+            }),
+        }
+    }
+
+    /// Queues `msg`, does nothing if the program is gone.
+    ///
+    /// If the update can't be spawned, the message stays queued until the next dispatch.
+    pub fn dispatch(&self, msg: Msg) -> Result<(), SpawnError> {
+        (self.dispatch)(msg)
+    }
+
+    /// Wraps the messages, e.g. to hand the dispatcher to an embedded program.
+    pub fn map<F, U>(&self, f: F) -> Dispatcher<U>
+    where
+        F: Fn(U) -> Msg + 'static,
+        U: 'static,
+    {
+        let dispatch = self.dispatch.clone();
+        Dispatcher {
+            dispatch: Rc::new(move |msg| dispatch(f(msg))),
+        }
+    }
+}
+
+impl<Msg> Clone for Dispatcher<Msg> {
+    fn clone(&self) -> Self {
+        Dispatcher {
+            dispatch: self.dispatch.clone(),
+        }
+    }
+}
+
+struct ProgramState<D, P, R>
+where
+    D: Driver,
+    P: Program<D>,
+{
+    program: P,
+    model: RefCell<P::Model>,
+    root: RefCell<R>,
+    queue: Rc<RefCell<MsgQueue<P::Msg>>>,
+    dispatcher: Dispatcher<P::Msg>,
+}
+
+/// Runs a `Program`, keeping its model and mounted view.
+///
+/// The program stops when the runner is dropped. Updates run in spawned futures, their errors are
+/// reported to the error handler of the driver, see `DriverCtx::report_error`.
+pub struct ProgramRunner<D, P, R>
+where
+    D: Driver,
+    P: Program<D>,
+{
+    state: Rc<ProgramState<D, P, R>>,
+}
+
+impl<D, P, R> ProgramRunner<D, P, R>
+where
+    D: Driver + 'static,
+    D::Err: From<SpawnError> + From<R::Err>,
+    P: Program<D>,
+    R: ProgramRoot<D, P::View> + 'static,
+{
+    /// Initializes the program and mounts its first view with `mount`, e.g. `App::new`.
+    pub fn new<F, E>(program: P, mount: F) -> Result<ProgramRunner<D, P, R>, E>
+    where
+        F: FnOnce(P::View) -> Result<R, E>,
+        E: From<SpawnError>,
+    {
+        let queue = Rc::new(RefCell::new(MsgQueue {
+            msgs: VecDeque::new(),
+            is_flush_spawned: false,
+            spawn_flush: None,
+        }));
+        let dispatcher = Dispatcher::new(Rc::downgrade(&queue));
+        let (model, cmd) = program.init(&dispatcher);
+        let root = mount(program.view(&model, &dispatcher))?;
+        let driver_ctx = root.driver_ctx();
+        let state = Rc::new(ProgramState {
+            program,
+            model: RefCell::new(model),
+            root: RefCell::new(root),
+            queue: queue.clone(),
+            dispatcher: dispatcher.clone(),
+        });
+
+        let weak_state = Rc::downgrade(&state);
+        let spawn_driver_ctx = driver_ctx.clone();
+        queue.borrow_mut().spawn_flush = Some(Rc::new(move || {
+            let weak_state = weak_state.clone();
+            let error_driver_ctx = spawn_driver_ctx.clone();
+            spawn_driver_ctx.with_mut(|drv| {
+                drv.spawn_local(future::ready(()).map(move |_| {
+                    if let Err(err) = flush(&weak_state) {
+                        error_driver_ctx.report_error(err);
+                    }
+                }))
+            })
+        }));
+
+        // the messages dispatched from `init` and the first view were only queued
+        if !queue.borrow().msgs.is_empty() {
+            schedule_flush(&queue)?;
+        }
+        spawn_cmd(&driver_ctx, dispatcher, cmd)?;
+        Ok(ProgramRunner { state })
+    }
+
+    pub fn dispatcher(&self) -> Dispatcher<P::Msg> {
+        self.state.dispatcher.clone()
+    }
+
+    /// Reads the model, e.g. from the components of the view while it's applied.
+    ///
+    /// Panics if called from `Program::update` or `Program::view`, which get the model anyway.
+    pub fn with_model<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&P::Model) -> T,
+    {
+        f(&self.state.model.borrow())
+    }
+}
+
+/// Updates the program with all the queued messages, then renders and applies the view once.
+fn flush<D, P, R>(state: &Weak<ProgramState<D, P, R>>) -> Result<(), D::Err>
+where
+    D: Driver + 'static,
+    D::Err: From<SpawnError> + From<R::Err>,
+    P: Program<D>,
+    R: ProgramRoot<D, P::View>,
+{
+    let state = match state.upgrade() {
+        Some(state) => state,
+        None => return Ok(()),
+    };
+    let mut cmds = Vec::new();
+    let view = {
+        let model = &mut *state.model.borrow_mut();
+        loop {
+            // messages dispatched from `update` are handled in the same batch
+            let msg = {
+                let queue = &mut *state.queue.borrow_mut();
+                match queue.msgs.pop_front() {
+                    Some(msg) => msg,
+                    None => {
+                        queue.is_flush_spawned = false;
+                        break;
+                    }
+                }
+            };
+            cmds.push(state.program.update(model, msg));
+        }
+        state.program.view(model, &state.dispatcher)
+    };
+
+    // the model is released first, so it can be read while the view is applied
+    let root = &mut *state.root.borrow_mut();
+    root.set(view)?;
+    spawn_cmd(
+        &root.driver_ctx(),
+        state.dispatcher.clone(),
+        Cmd::batch(cmds),
+    )?;
+    Ok(())
+}
+
+fn spawn_cmd<D, Msg>(
+    driver_ctx: &DriverCtx<D>,
+    dispatcher: Dispatcher<Msg>,
+    cmd: Cmd<Msg>,
+) -> Result<(), SpawnError>
+where
+    D: Driver + 'static,
+    D::Err: From<SpawnError>,
+    Msg: 'static,
+{
+    if cmd.is_none() {
+        return Ok(());
+    }
+    driver_ctx.with_mut(|drv| {
+        for fut in cmd.futures {
+            let dispatcher = dispatcher.clone();
+            let driver_ctx = driver_ctx.clone();
+            drv.spawn_local(fut.map(move |msg| {
+                if let Some(msg) = msg {
+                    if let Err(err) = dispatcher.dispatch(msg) {
+                        driver_ctx.report_error(err.into());
+                    }
+                }
+            }))?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::vdom::node::{Text, TextDyn};
+    use futures::{
+        channel::oneshot,
+        executor::{LocalPool, LocalSpawner},
+        future::LocalFutureObj,
+        task::LocalSpawn,
+    };
+
+    /// The rendered views and the reported errors, in order.
+    pub(crate) type Log = Rc<RefCell<Vec<String>>>;
+
+    pub(crate) struct TestDriver {
+        spawner: LocalSpawner,
+        log: Log,
+    }
+
+    impl Driver for TestDriver {
+        type AttrStore = ();
+        type TagStore = ();
+        type TextStore = ();
+        type CommentStore = ();
+        type CompStore = ();
+        type PortalStore = ();
+        type RawHtmlStore = ();
+        type Element = ();
+        type Err = SpawnError;
+
+        fn new_attr_store() {}
+        fn new_tag_store() {}
+        fn new_text_store() {}
+        fn new_comment_store() {}
+        fn new_comp_store() {}
+        fn new_portal_store() {}
+        fn new_raw_html_store() {}
+
+        fn request_render(&mut self) {}
+
+        fn error_handler(&self) -> Rc<dyn Fn(SpawnError)> {
+            let log = self.log.clone();
+            Rc::new(move |_| log.borrow_mut().push("error".to_owned()))
+        }
+    }
+
+    impl LocalSpawn for TestDriver {
+        fn spawn_local_obj(
+            &mut self,
+            future: LocalFutureObj<'static, ()>,
+        ) -> Result<(), SpawnError> {
+            self.spawner.spawn_local_obj(future)
+        }
+    }
+
+    /// Logs the text of every view, failing on the ones starting with `-`.
+    pub(crate) struct TestRoot {
+        driver_ctx: DriverCtx<TestDriver>,
+        log: Log,
+    }
+
+    impl ProgramRoot<TestDriver, TextDyn<TestDriver>> for TestRoot {
+        type Err = SpawnError;
+
+        fn driver_ctx(&self) -> DriverCtx<TestDriver> {
+            self.driver_ctx.clone()
+        }
+
+        fn set(&mut self, node: TextDyn<TestDriver>) -> Result<(), SpawnError> {
+            if node.get().starts_with('-') {
+                return Err(SpawnError::shutdown());
+            }
+            self.log.borrow_mut().push(node.get().to_owned());
+            Ok(())
+        }
+    }
+
+    pub(crate) fn start<P>(
+        pool: &LocalPool,
+        program: P,
+    ) -> (ProgramRunner<TestDriver, P, TestRoot>, Log)
+    where
+        P: Program<TestDriver, View = TextDyn<TestDriver>>,
+    {
+        let log = Log::default();
+        let driver_ctx = DriverCtx::new(TestDriver {
+            spawner: pool.spawner(),
+            log: log.clone(),
+        });
+        let mut root = TestRoot {
+            driver_ctx,
+            log: log.clone(),
+        };
+        let runner = ProgramRunner::new(program, |view| root.set(view).map(|_| root)).unwrap();
+        (runner, log)
+    }
+
+    /// Runs the futures, including the ones spawned by them, which `run_until_stalled` leaves
+    /// for its next call.
+    pub(crate) fn run(pool: &mut LocalPool) {
+        pool.run_until_stalled();
+        pool.run_until_stalled();
+    }
+
+    struct Counter;
+
+    enum CounterMsg {
+        Add(i32),
+        /// Adds the number received from the channel.
+        Fetch(oneshot::Receiver<i32>),
+    }
+
+    impl Program<TestDriver> for Counter {
+        type Model = i32;
+        type Msg = CounterMsg;
+        type View = TextDyn<TestDriver>;
+
+        fn init(&self, _dispatcher: &Dispatcher<CounterMsg>) -> (i32, Cmd<CounterMsg>) {
+            (0, Cmd::none())
+        }
+
+        fn update(&self, model: &mut i32, msg: CounterMsg) -> Cmd<CounterMsg> {
+            match msg {
+                CounterMsg::Add(n) => {
+                    *model += n;
+                    Cmd::none()
+                }
+                CounterMsg::Fetch(n) => Cmd::new(n.map(|n| CounterMsg::Add(n.unwrap_or(0)))),
+            }
+        }
+
+        fn view(&self, model: &i32, _dispatcher: &Dispatcher<CounterMsg>) -> TextDyn<TestDriver> {
+            TextDyn::new(model.to_string())
+        }
+    }
+
+    /// Counts down to zero, dispatching every step from its view.
+    struct Countdown(i32);
+
+    impl Program<TestDriver> for Countdown {
+        type Model = i32;
+        type Msg = i32;
+        type View = TextDyn<TestDriver>;
+
+        fn init(&self, _dispatcher: &Dispatcher<i32>) -> (i32, Cmd<i32>) {
+            (self.0, Cmd::none())
+        }
+
+        fn update(&self, model: &mut i32, msg: i32) -> Cmd<i32> {
+            *model += msg;
+            Cmd::none()
+        }
+
+        fn view(&self, model: &i32, dispatcher: &Dispatcher<i32>) -> TextDyn<TestDriver> {
+            if *model > 0 {
+                dispatcher.dispatch(-1).unwrap();
+            }
+            TextDyn::new(model.to_string())
+        }
+    }
+
+    /// A dispatcher of no program, for programs run without a `ProgramRunner`.
+    pub(crate) fn detached_dispatcher<Msg>() -> Dispatcher<Msg>
+    where
+        Msg: 'static,
+    {
+        Dispatcher::new(Weak::new())
+    }
+
+    #[test]
+    fn batches_dispatches_into_one_render() {
+        let mut pool = LocalPool::new();
+        let (runner, log) = start(&pool, Counter);
+        let dispatcher = runner.dispatcher();
+        dispatcher.dispatch(CounterMsg::Add(1)).unwrap();
+        dispatcher.dispatch(CounterMsg::Add(2)).unwrap();
+        dispatcher.dispatch(CounterMsg::Add(3)).unwrap();
+        assert_eq!(*log.borrow(), ["0"]);
+
+        run(&mut pool);
+        assert_eq!(*log.borrow(), ["0", "6"]);
+        assert_eq!(runner.with_model(|model| *model), 6);
+    }
+
+    #[test]
+    fn dispatches_the_messages_of_commands() {
+        let mut pool = LocalPool::new();
+        let (runner, log) = start(&pool, Counter);
+        let (sender, receiver) = oneshot::channel();
+        runner
+            .dispatcher()
+            .dispatch(CounterMsg::Fetch(receiver))
+            .unwrap();
+        run(&mut pool);
+        assert_eq!(*log.borrow(), ["0", "0"]);
+
+        sender.send(5).unwrap();
+        run(&mut pool);
+        assert_eq!(*log.borrow(), ["0", "0", "5"]);
+    }
+
+    #[test]
+    fn reports_views_failing_to_apply() {
+        let mut pool = LocalPool::new();
+        let (runner, log) = start(&pool, Counter);
+        runner.dispatcher().dispatch(CounterMsg::Add(-1)).unwrap();
+        run(&mut pool);
+        assert_eq!(*log.borrow(), ["0", "error"]);
+
+        runner.dispatcher().dispatch(CounterMsg::Add(2)).unwrap();
+        run(&mut pool);
+        assert_eq!(log.borrow().last().unwrap(), "1");
+    }
+
+    #[test]
+    fn handles_messages_dispatched_from_views() {
+        let mut pool = LocalPool::new();
+        let (runner, log) = start(&pool, Countdown(2));
+        assert_eq!(*log.borrow(), ["2"]);
+
+        run(&mut pool);
+        run(&mut pool);
+        assert_eq!(*log.borrow(), ["2", "1", "0"]);
+        assert_eq!(runner.with_model(|model| *model), 0);
+    }
+}
@@ -0,0 +1,162 @@
+use super::*;
+use std::fmt;
+
+/// The `class` attribute as a list of class names that can be switched on and off individually.
+pub struct ClassList<D>
+where
+    D: Driver,
+{
+    classes: Vec<(Cow<'static, str>, bool)>,
+    driver_store: D::AttrStore,
+}
+
+impl<D> ClassList<D>
+where
+    D: Driver,
+{
+    pub fn new() -> ClassList<D> {
+        ClassList {
+            classes: Vec::new(),
+            driver_store: D::new_attr_store(),
+        }
+    }
+
+    /// Switches the class on or off, keeping its position if it's already listed.
+    pub fn set<C>(&mut self, class: C, active: bool)
+    where
+        C: Into<Cow<'static, str>>,
+    {
+        let class = class.into();
+        match self.classes.iter_mut().find(|(c, _)| *c == class) {
+            Some(entry) => entry.1 = active,
+            None => self.classes.push((class, active)),
+        }
+    }
+
+    pub fn with<C>(mut self, class: C, active: bool) -> ClassList<D>
+    where
+        C: Into<Cow<'static, str>>,
+    {
+        self.set(class, active);
+        self
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.iter().any(|c| c == class)
+    }
+
+    /// Iterates over the active classes, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.classes
+            .iter()
+            .filter(|(_, active)| *active)
+            .map(|(class, _)| class.as_ref())
+    }
+
+    /// The classes that have to be added or removed to turn `ancestor` into `self`.
+    pub fn changes<'a>(
+        &'a self,
+        ancestor: &'a ClassList<D>,
+    ) -> impl Iterator<Item = ClassChange<'a>> {
+        let added = self
+            .iter()
+            .filter(move |class| !ancestor.contains(class))
+            .map(ClassChange::Added);
+        let removed = ancestor
+            .iter()
+            .filter(move |class| !self.contains(class))
+            .map(ClassChange::Removed);
+        added.chain(removed)
+    }
+
+    pub fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D> Default for ClassList<D>
+where
+    D: Driver,
+{
+    fn default() -> Self {
+        ClassList::new()
+    }
+}
+
+/// Serializes the active classes in the `class` attribute syntax.
+impl<D> fmt::Display for ClassList<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, class) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(class)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D> AttrList<D> for ClassList<D>
+where
+    D: Driver,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        visitor.on_class_list(self)
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        differ.on_class_list(self, ancestor)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClassChange<'a> {
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::tests::TestDriver;
+
+    #[test]
+    fn switches_listed_classes() {
+        let mut classes = ClassList::<TestDriver>::new()
+            .with("a", true)
+            .with("b", true)
+            .with("a", false);
+        assert!(!classes.contains("a"));
+        assert_eq!(classes.to_string(), "b");
+
+        classes.set("a", true);
+        assert!(classes.contains("a"));
+        assert_eq!(classes.to_string(), "a b");
+    }
+
+    #[test]
+    fn lists_added_and_removed_classes() {
+        let ancestor = ClassList::<TestDriver>::new()
+            .with("a", true)
+            .with("b", true)
+            .with("c", false);
+        let curr = ClassList::new()
+            .with("a", true)
+            .with("b", false)
+            .with("c", true);
+        assert_eq!(
+            curr.changes(&ancestor).collect::<Vec<_>>(),
+            [ClassChange::Added("c"), ClassChange::Removed("b")]
+        );
+        assert_eq!(curr.changes(&curr).count(), 0);
+    }
+}
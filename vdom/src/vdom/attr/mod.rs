@@ -0,0 +1,971 @@
+mod class_list;
+mod node_ref;
+mod signal_attr;
+mod style;
+
+use std::{borrow::Cow, collections::BTreeMap, fmt, iter::FromIterator, mem, rc::Rc};
+
+pub use self::class_list::*;
+pub use self::node_ref::*;
+pub use self::signal_attr::*;
+pub use self::style::*;
+use crate::{
+    driver::Driver,
+    vdom::{namespace::Namespace, signal::ReadSignal},
+};
+
+/// Floats are compared by their bits, like their formatted values, so `AttrValue` is `Eq`.
+#[derive(Clone)]
+pub enum AttrValue {
+    True,
+    Null,
+    Str(&'static str),
+    String(String),
+    Rc(Rc<str>),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+}
+
+impl PartialEq for AttrValue {
+    fn eq(&self, other: &AttrValue) -> bool {
+        match (self, other) {
+            (AttrValue::True, AttrValue::True) | (AttrValue::Null, AttrValue::Null) => true,
+            (AttrValue::Str(a), AttrValue::Str(b)) => a == b,
+            (AttrValue::String(a), AttrValue::String(b)) => a == b,
+            (AttrValue::Rc(a), AttrValue::Rc(b)) => a == b,
+            (AttrValue::Int(a), AttrValue::Int(b)) => a == b,
+            (AttrValue::UInt(a), AttrValue::UInt(b)) => a == b,
+            (AttrValue::Float(a), AttrValue::Float(b)) => a.to_bits() == b.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AttrValue {}
+
+impl From<bool> for AttrValue {
+    fn from(v: bool) -> AttrValue {
+        match v {
+            true => AttrValue::True,
+            false => AttrValue::Null,
+        }
+    }
+}
+
+impl From<Option<()>> for AttrValue {
+    fn from(v: Option<()>) -> AttrValue {
+        match v {
+            Some(()) => AttrValue::True,
+            None => AttrValue::Null,
+        }
+    }
+}
+
+impl From<&'static str> for AttrValue {
+    fn from(v: &'static str) -> AttrValue {
+        AttrValue::Str(v)
+    }
+}
+
+impl From<Option<&'static str>> for AttrValue {
+    fn from(v: Option<&'static str>) -> AttrValue {
+        match v {
+            Some(s) => AttrValue::Str(s),
+            None => AttrValue::Null,
+        }
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(v: String) -> AttrValue {
+        AttrValue::String(v)
+    }
+}
+
+impl From<Option<String>> for AttrValue {
+    fn from(v: Option<String>) -> AttrValue {
+        match v {
+            Some(s) => AttrValue::String(s),
+            None => AttrValue::Null,
+        }
+    }
+}
+
+impl From<Cow<'static, str>> for AttrValue {
+    fn from(v: Cow<'static, str>) -> AttrValue {
+        match v {
+            Cow::Borrowed(s) => AttrValue::Str(s),
+            Cow::Owned(s) => AttrValue::String(s),
+        }
+    }
+}
+
+impl From<Option<Cow<'static, str>>> for AttrValue {
+    fn from(v: Option<Cow<'static, str>>) -> AttrValue {
+        v.map_or(AttrValue::Null, From::from)
+    }
+}
+
+impl From<Rc<str>> for AttrValue {
+    fn from(v: Rc<str>) -> AttrValue {
+        AttrValue::Rc(v)
+    }
+}
+
+impl From<Option<Rc<str>>> for AttrValue {
+    fn from(v: Option<Rc<str>>) -> AttrValue {
+        v.map_or(AttrValue::Null, From::from)
+    }
+}
+
+macro_rules! impl_from_num {
+    ($variant:ident, $repr:ty, $($ty:ty),*) => {
+        $(
+            impl From<$ty> for AttrValue {
+                fn from(v: $ty) -> AttrValue {
+                    AttrValue::$variant(v as $repr)
+                }
+            }
+
+            impl From<Option<$ty>> for AttrValue {
+                fn from(v: Option<$ty>) -> AttrValue {
+                    v.map_or(AttrValue::Null, From::from)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_num!(Int, i64, i8, i16, i32, i64, isize);
+impl_from_num!(UInt, u64, u8, u16, u32, u64, usize);
+impl_from_num!(Float, f64, f32, f64);
+
+/// A borrowed attribute value; numbers are kept typed, so comparing them doesn't allocate.
+///
+/// Floats are compared by their bits, as in `AttrValue`.
+#[derive(Clone, Copy)]
+pub enum AttrRefValue<'a> {
+    True,
+    Null,
+    Str(&'a str),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+}
+
+impl<'a> PartialEq for AttrRefValue<'a> {
+    fn eq(&self, other: &AttrRefValue<'a>) -> bool {
+        match (*self, *other) {
+            (AttrRefValue::True, AttrRefValue::True) | (AttrRefValue::Null, AttrRefValue::Null) => {
+                true
+            }
+            (AttrRefValue::Str(a), AttrRefValue::Str(b)) => a == b,
+            (AttrRefValue::Int(a), AttrRefValue::Int(b)) => a == b,
+            (AttrRefValue::UInt(a), AttrRefValue::UInt(b)) => a == b,
+            (AttrRefValue::Float(a), AttrRefValue::Float(b)) => a.to_bits() == b.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Eq for AttrRefValue<'a> {}
+
+impl<'a> AttrRefValue<'a> {
+    /// The value as written to the document, formatting numbers only now.
+    ///
+    /// `True` attributes take their own `name` as value, `Null` ones are not set at all.
+    pub fn to_str(&self, name: &'a str) -> Option<Cow<'a, str>> {
+        match *self {
+            AttrRefValue::True => Some(Cow::Borrowed(name)),
+            AttrRefValue::Null => None,
+            AttrRefValue::Str(s) => Some(Cow::Borrowed(s)),
+            AttrRefValue::Int(v) => Some(Cow::Owned(v.to_string())),
+            AttrRefValue::UInt(v) => Some(Cow::Owned(v.to_string())),
+            AttrRefValue::Float(v) => Some(Cow::Owned(v.to_string())),
+        }
+    }
+}
+
+impl<'a> From<&'a AttrValue> for AttrRefValue<'a> {
+    fn from(v: &'a AttrValue) -> AttrRefValue<'a> {
+        match v {
+            AttrValue::True => AttrRefValue::True,
+            AttrValue::Null => AttrRefValue::Null,
+            AttrValue::Str(s) => AttrRefValue::Str(s),
+            AttrValue::String(s) => AttrRefValue::Str(s.as_str()),
+            AttrValue::Rc(s) => AttrRefValue::Str(s),
+            AttrValue::Int(v) => AttrRefValue::Int(*v),
+            AttrValue::UInt(v) => AttrRefValue::UInt(*v),
+            AttrValue::Float(v) => AttrRefValue::Float(*v),
+        }
+    }
+}
+
+pub trait Attr<D>
+where
+    D: Driver,
+{
+    /// Whether the value is the same on every render, see `Node::IS_STATIC`.
+    const IS_STATIC: bool = false;
+
+    fn is_value_static(&self) -> bool;
+    fn name(&self) -> &str;
+    fn value(&self) -> AttrRefValue<'_>;
+
+    fn namespace(&self) -> Option<Namespace> {
+        Namespace::of_attr(self.name())
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore;
+}
+
+pub struct AttrTrue<D>
+where
+    D: Driver,
+{
+    key: &'static str,
+    driver_store: D::AttrStore,
+}
+
+impl<D> AttrTrue<D>
+where
+    D: Driver,
+{
+    pub fn new(key: &'static str) -> AttrTrue<D> {
+        AttrTrue {
+            key,
+            driver_store: D::new_attr_store(),
+        }
+    }
+}
+
+impl<D> Attr<D> for AttrTrue<D>
+where
+    D: Driver,
+{
+    const IS_STATIC: bool = true;
+
+    fn is_value_static(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        AttrRefValue::True
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+pub struct AttrStr<D>
+where
+    D: Driver,
+{
+    key: &'static str,
+    value: &'static str,
+    driver_store: D::AttrStore,
+}
+
+impl<D> AttrStr<D>
+where
+    D: Driver,
+{
+    pub fn new(key: &'static str, value: &'static str) -> AttrStr<D> {
+        AttrStr {
+            key,
+            value,
+            driver_store: D::new_attr_store(),
+        }
+    }
+}
+
+impl<D> Attr<D> for AttrStr<D>
+where
+    D: Driver,
+{
+    const IS_STATIC: bool = true;
+
+    fn is_value_static(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        AttrRefValue::Str(self.value)
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+pub struct AttrDyn<D>
+where
+    D: Driver,
+{
+    key: &'static str,
+    value: AttrValue,
+    driver_store: D::AttrStore,
+}
+
+impl<D> AttrDyn<D>
+where
+    D: Driver,
+{
+    pub fn new<V>(key: &'static str, value: V) -> AttrDyn<D>
+    where
+        V: Into<AttrValue>,
+    {
+        AttrDyn {
+            key,
+            value: value.into(),
+            driver_store: D::new_attr_store(),
+        }
+    }
+}
+
+impl<D> Attr<D> for AttrDyn<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        (&self.value).into()
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+/// An attribute formatting its value with `Display`, only when it changed.
+///
+/// Added to a tag with `..(DisplayAttr::new("width", width))` in `html!`.
+pub struct DisplayAttr<D, T>
+where
+    D: Driver,
+{
+    key: &'static str,
+    value: T,
+    text: Option<String>,
+    driver_store: D::AttrStore,
+}
+
+impl<D, T> DisplayAttr<D, T>
+where
+    D: Driver,
+    T: fmt::Display + PartialEq,
+{
+    pub fn new(key: &'static str, value: T) -> DisplayAttr<D, T> {
+        DisplayAttr {
+            key,
+            value,
+            text: None,
+            driver_store: D::new_attr_store(),
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn format(&mut self) {
+        if self.text.is_none() {
+            self.text = Some(self.value.to_string());
+        }
+    }
+}
+
+impl<D, T> Attr<D> for DisplayAttr<D, T>
+where
+    D: Driver,
+    T: fmt::Display + PartialEq,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    /// Empty until the attribute was visited or diffed.
+    fn value(&self) -> AttrRefValue<'_> {
+        AttrRefValue::Str(self.text.as_ref().map_or("", String::as_str))
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D, T> AttrList<D> for DisplayAttr<D, T>
+where
+    D: Driver,
+    T: fmt::Display + PartialEq,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        self.format();
+        visitor.on_attr(self)
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        debug_assert_eq!(self.key, ancestor.key);
+        if self.value == ancestor.value {
+            mem::swap(&mut self.text, &mut ancestor.text);
+            mem::swap(&mut self.driver_store, &mut ancestor.driver_store);
+            Ok(())
+        } else {
+            self.format();
+            differ.on_diff(self, ancestor)
+        }
+    }
+}
+
+pub trait AttrVisitor<D>
+where
+    D: Driver,
+{
+    type Err;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<D>;
+
+    fn on_style(&mut self, style: &mut Style<D>) -> Result<(), Self::Err>;
+
+    fn on_class_list(&mut self, class_list: &mut ClassList<D>) -> Result<(), Self::Err>;
+
+    fn on_node_ref(&mut self, node_ref: &mut NodeRef<D>) -> Result<(), Self::Err>;
+
+    fn on_signal_attr<S>(&mut self, attr: &mut SignalAttr<D, S>) -> Result<(), Self::Err>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>;
+}
+
+pub trait AttrDiffer<D>
+where
+    D: Driver,
+{
+    type Err;
+
+    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<D>;
+
+    /// Called for attributes of an `AttrMap` that the ancestor didn't have.
+    fn on_attr_added<A>(&mut self, curr: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<D>;
+
+    /// Called for attributes of the ancestor's `AttrMap` that are gone now.
+    fn on_attr_removed<A>(&mut self, ancestor: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<D>;
+
+    fn on_style(&mut self, curr: &mut Style<D>, ancestor: &mut Style<D>) -> Result<(), Self::Err>;
+
+    fn on_class_list(
+        &mut self,
+        curr: &mut ClassList<D>,
+        ancestor: &mut ClassList<D>,
+    ) -> Result<(), Self::Err>;
+
+    fn on_node_ref(
+        &mut self,
+        curr: &mut NodeRef<D>,
+        ancestor: &mut NodeRef<D>,
+    ) -> Result<(), Self::Err>;
+
+    /// Called for attributes bound to a signal, which may be bound to another signal now.
+    fn on_signal_attr<S>(
+        &mut self,
+        curr: &mut SignalAttr<D, S>,
+        ancestor: &mut SignalAttr<D, S>,
+    ) -> Result<(), Self::Err>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>;
+}
+
+pub trait AttrList<D>
+where
+    Self: Sized,
+    D: Driver,
+{
+    /// Whether all the attributes are the same on every render, see `Node::IS_STATIC`.
+    const IS_STATIC: bool = false;
+
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>;
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>;
+
+    fn push<A>(self, attr: A) -> (Self, AttrListEntry<A>)
+    where
+        A: Attr<D>,
+    {
+        (self, AttrListEntry(attr))
+    }
+}
+
+macro_rules! impl_attr_list_tuple {
+    ($($ty:ident: $idx:tt),*) => {
+        impl<D, $($ty),*> AttrList<D> for ($($ty,)*)
+        where
+            D: Driver,
+            $($ty: AttrList<D>,)*
+        {
+            const IS_STATIC: bool = $($ty::IS_STATIC)&&*;
+
+            fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+            where
+                AV: AttrVisitor<D>,
+            {
+                $(self.$idx.visit(visitor)?;)*
+                Ok(())
+            }
+
+            fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+            where
+                AD: AttrDiffer<D>,
+            {
+                $(self.$idx.diff(&mut ancestor.$idx, differ)?;)*
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_attr_list_tuple!(L1: 0, L2: 1);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12, L14: 13);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12, L14: 13, L15: 14);
+impl_attr_list_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12, L14: 13, L15: 14, L16: 15);
+
+impl<D> AttrList<D> for ()
+where
+    D: Driver,
+{
+    const IS_STATIC: bool = true;
+
+    fn visit<AV>(&mut self, _visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        Ok(())
+    }
+
+    fn diff<AD>(&mut self, _ancestor: &mut Self, _differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        Ok(())
+    }
+}
+
+pub struct AttrListEntry<A>(pub A);
+
+impl<A, D> AttrList<D> for AttrListEntry<A>
+where
+    A: Attr<D>,
+    D: Driver,
+{
+    const IS_STATIC: bool = A::IS_STATIC;
+
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        visitor.on_attr(&mut self.0)
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        debug_assert_eq!(self.0.name(), ancestor.0.name());
+        diff_attr(&mut self.0, &mut ancestor.0, differ)
+    }
+}
+
+/// Only passes attributes to the differ, if their values changed.
+fn diff_attr<D, A, AD>(curr: &mut A, ancestor: &mut A, differ: &mut AD) -> Result<(), AD::Err>
+where
+    D: Driver,
+    A: Attr<D>,
+    AD: AttrDiffer<D>,
+{
+    if curr.value() == ancestor.value() {
+        mem::swap(curr.driver_store(), ancestor.driver_store());
+        Ok(())
+    } else {
+        differ.on_diff(curr, ancestor)
+    }
+}
+
+struct AttrMapValue<D>
+where
+    D: Driver,
+{
+    value: AttrValue,
+    driver_store: D::AttrStore,
+}
+
+/// An attribute list with keys known only at runtime, e.g. `data-*` attributes from a config or
+/// attributes forwarded to a component.
+pub struct AttrMap<D>
+where
+    D: Driver,
+{
+    entries: BTreeMap<Cow<'static, str>, AttrMapValue<D>>,
+}
+
+impl<D> AttrMap<D>
+where
+    D: Driver,
+{
+    pub fn new() -> AttrMap<D> {
+        AttrMap {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<AttrValue>,
+    {
+        self.entries.insert(
+            key.into(),
+            AttrMapValue {
+                value: value.into(),
+                driver_store: D::new_attr_store(),
+            },
+        );
+    }
+
+    pub fn with<K, V>(mut self, key: K, value: V) -> AttrMap<D>
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<AttrValue>,
+    {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&AttrValue> {
+        self.entries.get(key).map(|entry| &entry.value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<D> Default for AttrMap<D>
+where
+    D: Driver,
+{
+    fn default() -> Self {
+        AttrMap::new()
+    }
+}
+
+impl<D, K, V> FromIterator<(K, V)> for AttrMap<D>
+where
+    D: Driver,
+    K: Into<Cow<'static, str>>,
+    V: Into<AttrValue>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = AttrMap::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<D> AttrList<D> for AttrMap<D>
+where
+    D: Driver,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        for (key, entry) in self.entries.iter_mut() {
+            visitor.on_attr(&mut AttrMapEntry::new(key, entry))?;
+        }
+        Ok(())
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        for (key, entry) in self.entries.iter_mut() {
+            let mut curr = AttrMapEntry::new(key, entry);
+            match ancestor.entries.get_mut(key) {
+                Some(ancestor_entry) => {
+                    diff_attr(
+                        &mut curr,
+                        &mut AttrMapEntry::new(key, ancestor_entry),
+                        differ,
+                    )?
+                }
+                None => differ.on_attr_added(&mut curr)?,
+            }
+        }
+        for (key, ancestor_entry) in ancestor.entries.iter_mut() {
+            if !self.entries.contains_key(key) {
+                differ.on_attr_removed(&mut AttrMapEntry::new(key, ancestor_entry))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An attribute of an `AttrMap`, as passed to the visitors and differs.
+pub struct AttrMapEntry<'a, D>
+where
+    D: Driver,
+{
+    key: &'a str,
+    value: &'a AttrValue,
+    driver_store: &'a mut D::AttrStore,
+}
+
+impl<'a, D> AttrMapEntry<'a, D>
+where
+    D: Driver,
+{
+    fn new(key: &'a str, entry: &'a mut AttrMapValue<D>) -> AttrMapEntry<'a, D> {
+        AttrMapEntry {
+            key,
+            value: &entry.value,
+            driver_store: &mut entry.driver_store,
+        }
+    }
+}
+
+impl<'a, D> Attr<D> for AttrMapEntry<'a, D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        self.value.into()
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        self.driver_store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::tests::TestDriver;
+
+    /// Records the changes reported for an `AttrMap`.
+    #[derive(Default)]
+    struct MapDiffer {
+        changes: Vec<String>,
+    }
+
+    fn attr_str<A>(attr: &A) -> String
+    where
+        A: Attr<TestDriver>,
+    {
+        let value = attr.value();
+        format!("{}={:?}", attr.name(), value.to_str(attr.name()))
+    }
+
+    impl AttrDiffer<TestDriver> for MapDiffer {
+        type Err = ();
+
+        fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), ()>
+        where
+            A: Attr<TestDriver>,
+        {
+            let change = format!("changed {} to {}", attr_str(ancestor), attr_str(curr));
+            self.changes.push(change);
+            Ok(())
+        }
+
+        fn on_attr_added<A>(&mut self, curr: &mut A) -> Result<(), ()>
+        where
+            A: Attr<TestDriver>,
+        {
+            self.changes.push(format!("added {}", attr_str(curr)));
+            Ok(())
+        }
+
+        fn on_attr_removed<A>(&mut self, ancestor: &mut A) -> Result<(), ()>
+        where
+            A: Attr<TestDriver>,
+        {
+            self.changes.push(format!("removed {}", attr_str(ancestor)));
+            Ok(())
+        }
+
+        fn on_style(
+            &mut self,
+            _curr: &mut Style<TestDriver>,
+            _ancestor: &mut Style<TestDriver>,
+        ) -> Result<(), ()> {
+            unreachable!()
+        }
+
+        fn on_class_list(
+            &mut self,
+            _curr: &mut ClassList<TestDriver>,
+            _ancestor: &mut ClassList<TestDriver>,
+        ) -> Result<(), ()> {
+            unreachable!()
+        }
+
+        fn on_node_ref(
+            &mut self,
+            _curr: &mut NodeRef<TestDriver>,
+            _ancestor: &mut NodeRef<TestDriver>,
+        ) -> Result<(), ()> {
+            unreachable!()
+        }
+
+        fn on_signal_attr<S>(
+            &mut self,
+            _curr: &mut SignalAttr<TestDriver, S>,
+            _ancestor: &mut SignalAttr<TestDriver, S>,
+        ) -> Result<(), ()>
+        where
+            S: ReadSignal,
+            S::Value: Clone + Into<AttrValue>,
+        {
+            unreachable!()
+        }
+    }
+
+    fn diff_maps(mut curr: AttrMap<TestDriver>, mut ancestor: AttrMap<TestDriver>) -> Vec<String> {
+        let mut differ = MapDiffer::default();
+        curr.diff(&mut ancestor, &mut differ).unwrap();
+        differ.changes
+    }
+
+    #[test]
+    fn diffs_attr_maps_by_key() {
+        let ancestor = AttrMap::new().with("id", "a").with("title", "t");
+        let curr = AttrMap::new()
+            .with("id", "b")
+            .with("title", "t")
+            .with("hidden", true);
+        assert_eq!(
+            diff_maps(curr, ancestor),
+            [
+                r#"added hidden=Some("hidden")"#,
+                r#"changed id=Some("a") to id=Some("b")"#,
+            ]
+        );
+
+        let ancestor = AttrMap::new().with("id", "a").with("tabindex", 1);
+        let curr = AttrMap::new().with("id", "a");
+        assert_eq!(diff_maps(curr, ancestor), [r#"removed tabindex=Some("1")"#]);
+    }
+
+    #[test]
+    fn matches_borrowed_and_owned_keys() {
+        let ancestor = AttrMap::new().with("data-id", 1);
+        let curr = AttrMap::new().with(String::from("data-id"), 1);
+        assert!(diff_maps(curr, ancestor).is_empty());
+
+        let ancestor = AttrMap::new().with(String::from("data-id"), 1);
+        let curr = AttrMap::new().with("data-id", 2);
+        assert_eq!(
+            diff_maps(curr, ancestor),
+            [r#"changed data-id=Some("1") to data-id=Some("2")"#]
+        );
+    }
+
+    #[test]
+    fn compares_values_by_type() {
+        assert!(AttrValue::from(1u32) == AttrValue::from(1u64));
+        assert!(AttrValue::from(1u32) != AttrValue::from(1i32));
+        assert!(AttrValue::from(f64::NAN) == AttrValue::from(f64::NAN));
+        assert!(AttrValue::from(0.0) != AttrValue::from(-0.0));
+        assert!(AttrRefValue::Float(0.5) == AttrRefValue::from(&AttrValue::from(0.5)));
+        assert!(AttrValue::from(-2i8) == AttrValue::from(-2isize));
+        assert!(AttrValue::from(2u8) != AttrValue::from(2i8));
+        assert!(AttrValue::from(0.5f32) == AttrValue::from(0.5f64));
+        assert!(AttrValue::from(Some(1.5f32)) == AttrValue::from(1.5));
+        assert!(AttrValue::from(None::<f32>) == AttrValue::Null);
+    }
+
+    #[test]
+    fn formats_numbers() {
+        let to_str = |value: AttrValue| AttrRefValue::from(&value).to_str("x").map(Cow::into_owned);
+        assert_eq!(to_str(AttrValue::from(-2i16)).unwrap(), "-2");
+        assert_eq!(
+            to_str(AttrValue::from(u64::MAX)).unwrap(),
+            "18446744073709551615"
+        );
+        assert_eq!(to_str(AttrValue::from(0.25f32)).unwrap(), "0.25");
+        assert_eq!(to_str(AttrValue::from(3.0)).unwrap(), "3");
+        assert_eq!(to_str(AttrValue::from(None::<u8>)), None);
+    }
+}
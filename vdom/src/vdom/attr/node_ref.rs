@@ -0,0 +1,88 @@
+use super::*;
+use std::cell::RefCell;
+
+/// A handle to the element of a tag, set by the driver while the tag is mounted.
+///
+/// Added to a tag with `ref=(node_ref)` in `html!`; clones share the same element.
+pub struct NodeRef<D>
+where
+    D: Driver,
+{
+    element: Rc<RefCell<Option<D::Element>>>,
+}
+
+impl<D> NodeRef<D>
+where
+    D: Driver,
+{
+    pub fn new() -> NodeRef<D> {
+        NodeRef {
+            element: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// The mounted element, `None` if the tag isn't mounted (yet).
+    pub fn get(&self) -> Option<D::Element> {
+        self.element.borrow().clone()
+    }
+
+    /// Called by the driver when the tag is mounted, or removed with `None`.
+    pub fn set(&self, element: Option<D::Element>) {
+        *self.element.borrow_mut() = element;
+    }
+
+    /// Whether both are handles to the same element.
+    pub fn ptr_eq(&self, other: &NodeRef<D>) -> bool {
+        Rc::ptr_eq(&self.element, &other.element)
+    }
+}
+
+impl<D> Clone for NodeRef<D>
+where
+    D: Driver,
+{
+    fn clone(&self) -> Self {
+        NodeRef {
+            element: self.element.clone(),
+        }
+    }
+}
+
+impl<D> PartialEq for NodeRef<D>
+where
+    D: Driver,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl<D> Eq for NodeRef<D> where D: Driver {}
+
+impl<D> Default for NodeRef<D>
+where
+    D: Driver,
+{
+    fn default() -> Self {
+        NodeRef::new()
+    }
+}
+
+impl<D> AttrList<D> for NodeRef<D>
+where
+    D: Driver,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        visitor.on_node_ref(self)
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        differ.on_node_ref(self, ancestor)
+    }
+}
@@ -0,0 +1,116 @@
+use super::*;
+use crate::vdom::signal::{ReadSignal, Subscription};
+
+/// An attribute bound to a signal, patched by the driver whenever the signal changes.
+///
+/// Like `SignalText`, a change doesn't render the component again. Added to a tag as a spread,
+/// e.g. `input ..(SignalAttr::new("value", &value));`.
+pub struct SignalAttr<D, S>
+where
+    D: Driver,
+{
+    key: &'static str,
+    signal: S,
+    /// The value at the time the attribute was created, used for mounting.
+    value: AttrValue,
+    binding: Option<Subscription>,
+    driver_store: D::AttrStore,
+}
+
+impl<D, S> SignalAttr<D, S>
+where
+    D: Driver,
+    S: ReadSignal,
+    S::Value: Clone + Into<AttrValue>,
+{
+    pub fn new(key: &'static str, signal: &S) -> SignalAttr<D, S> {
+        SignalAttr {
+            key,
+            signal: signal.clone(),
+            value: signal.get().into(),
+            binding: None,
+            driver_store: D::new_attr_store(),
+        }
+    }
+
+    pub fn signal(&self) -> &S {
+        &self.signal
+    }
+
+    /// Called by the driver once the attribute is mounted, `patch` gets the value as written to
+    /// the document on every change, `None` if the attribute is to be removed.
+    ///
+    /// The binding is dropped along with the attribute.
+    pub fn bind<F>(&mut self, patch: F)
+    where
+        F: Fn(Option<&str>) + 'static,
+    {
+        let key = self.key;
+        self.binding = Some(self.signal.subscribe(move |value| {
+            let value: AttrValue = value.clone().into();
+            patch(
+                AttrRefValue::from(&value)
+                    .to_str(key)
+                    .as_ref()
+                    .map(AsRef::as_ref),
+            );
+        }));
+    }
+
+    /// Takes over the binding of `ancestor` if both are bound to the same signal.
+    ///
+    /// Returns `false` if they aren't, in which case the driver has to patch and bind the
+    /// attribute.
+    pub fn adopt_binding(&mut self, ancestor: &mut Self) -> bool {
+        if self.key == ancestor.key && self.signal.ptr_eq(&ancestor.signal) {
+            self.binding = ancestor.binding.take();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<D, S> Attr<D> for SignalAttr<D, S>
+where
+    D: Driver,
+    S: ReadSignal,
+    S::Value: Clone + Into<AttrValue>,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        (&self.value).into()
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D, S> AttrList<D> for SignalAttr<D, S>
+where
+    D: Driver,
+    S: ReadSignal,
+    S::Value: Clone + Into<AttrValue>,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        visitor.on_signal_attr(self)
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        differ.on_signal_attr(self, ancestor)
+    }
+}
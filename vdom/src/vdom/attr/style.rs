@@ -0,0 +1,169 @@
+use super::*;
+use std::fmt;
+
+/// The inline `style` attribute as a map of CSS properties, diffed per property.
+pub struct Style<D>
+where
+    D: Driver,
+{
+    props: BTreeMap<Cow<'static, str>, Cow<'static, str>>,
+    driver_store: D::AttrStore,
+}
+
+impl<D> Style<D>
+where
+    D: Driver,
+{
+    pub fn new() -> Style<D> {
+        Style {
+            props: BTreeMap::new(),
+            driver_store: D::new_attr_store(),
+        }
+    }
+
+    pub fn set<P, V>(&mut self, prop: P, value: V)
+    where
+        P: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        self.props.insert(prop.into(), value.into());
+    }
+
+    pub fn with<P, V>(mut self, prop: P, value: V) -> Style<D>
+    where
+        P: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        self.set(prop, value);
+        self
+    }
+
+    pub fn remove(&mut self, prop: &str) {
+        self.props.remove(prop);
+    }
+
+    pub fn get(&self, prop: &str) -> Option<&str> {
+        self.props.get(prop).map(|value| value.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.props.is_empty()
+    }
+
+    /// Iterates over the properties, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.props
+            .iter()
+            .map(|(prop, value)| (prop.as_ref(), value.as_ref()))
+    }
+
+    /// The properties that have to be set or removed to turn `ancestor` into `self`.
+    pub fn changes<'a>(&'a self, ancestor: &'a Style<D>) -> impl Iterator<Item = StyleChange<'a>> {
+        let set = self.iter().filter_map(move |(prop, value)| {
+            if ancestor.get(prop) == Some(value) {
+                None
+            } else {
+                Some(StyleChange::Set(prop, value))
+            }
+        });
+        let removed = ancestor.props.keys().filter_map(move |prop| {
+            if self.props.contains_key(prop) {
+                None
+            } else {
+                Some(StyleChange::Removed(prop.as_ref()))
+            }
+        });
+        set.chain(removed)
+    }
+
+    pub fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D> Default for Style<D>
+where
+    D: Driver,
+{
+    fn default() -> Self {
+        Style::new()
+    }
+}
+
+/// Serializes the properties in the `style` attribute syntax, ordered by name so the output is
+/// deterministic.
+impl<D> fmt::Display for Style<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (prop, value)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}: {};", prop, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D> AttrList<D> for Style<D>
+where
+    D: Driver,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        visitor.on_style(self)
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        differ.on_style(self, ancestor)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StyleChange<'a> {
+    Set(&'a str, &'a str),
+    Removed(&'a str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::tests::TestDriver;
+
+    fn style(props: &[(&'static str, &'static str)]) -> Style<TestDriver> {
+        props.iter().fold(Style::new(), |style, &(prop, value)| {
+            style.with(prop, value)
+        })
+    }
+
+    #[test]
+    fn lists_changed_and_removed_props() {
+        let ancestor = style(&[("color", "red"), ("margin", "0"), ("top", "1px")]);
+        let curr = style(&[("color", "blue"), ("margin", "0"), ("left", "2px")]);
+        assert_eq!(
+            curr.changes(&ancestor).collect::<Vec<_>>(),
+            [
+                StyleChange::Set("color", "blue"),
+                StyleChange::Set("left", "2px"),
+                StyleChange::Removed("top"),
+            ]
+        );
+        assert_eq!(curr.changes(&curr).count(), 0);
+    }
+
+    #[test]
+    fn serializes_props_ordered_by_name() {
+        let mut style = style(&[("margin", "0 auto"), ("color", "red")]);
+        assert_eq!(style.to_string(), "color: red; margin: 0 auto;");
+        style.remove("color");
+        assert_eq!(style.to_string(), "margin: 0 auto;");
+        assert_eq!(Style::<TestDriver>::new().to_string(), "");
+    }
+}
@@ -1,2 +1,5 @@
 pub mod attr;
+pub mod namespace;
 pub mod node;
+pub mod sanitize;
+pub mod signal;
@@ -0,0 +1,74 @@
+/// The XML namespace elements and attributes are created in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Namespace {
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+    XLink,
+    Xml,
+    XmlNs,
+}
+
+impl Namespace {
+    pub fn uri(self) -> &'static str {
+        match self {
+            Namespace::Html => "http://www.w3.org/1999/xhtml",
+            Namespace::Svg => "http://www.w3.org/2000/svg",
+            Namespace::MathMl => "http://www.w3.org/1998/Math/MathML",
+            Namespace::XLink => "http://www.w3.org/1999/xlink",
+            Namespace::Xml => "http://www.w3.org/XML/1998/namespace",
+            Namespace::XmlNs => "http://www.w3.org/2000/xmlns/",
+        }
+    }
+
+    pub fn from_uri(uri: &str) -> Option<Namespace> {
+        [
+            Namespace::Html,
+            Namespace::Svg,
+            Namespace::MathMl,
+            Namespace::XLink,
+            Namespace::Xml,
+            Namespace::XmlNs,
+        ]
+        .iter()
+        .cloned()
+        .find(|ns| ns.uri() == uri)
+    }
+
+    /// The namespace of an element named `tag`, placed in an element of the `parent` namespace.
+    ///
+    /// `<svg>` and `<math>` switch into their namespace, everything else inherits it.
+    pub fn of_element(tag: &str, parent: Namespace) -> Namespace {
+        match tag {
+            "svg" => Namespace::Svg,
+            "math" => Namespace::MathMl,
+            _ => parent,
+        }
+    }
+
+    /// The namespace of the children of a `tag` element in this namespace.
+    ///
+    /// The content of `<foreignObject>` is back in HTML.
+    pub fn of_children(self, tag: &str) -> Namespace {
+        match (self, tag) {
+            (Namespace::Svg, "foreignObject") => Namespace::Html,
+            _ => self,
+        }
+    }
+
+    /// The namespace of an attribute, given by the prefix of its name (e.g. `xlink:href`).
+    ///
+    /// Attributes without a known prefix aren't in any namespace.
+    pub fn of_attr(name: &str) -> Option<Namespace> {
+        if name == "xmlns" || name.starts_with("xmlns:") {
+            Some(Namespace::XmlNs)
+        } else if name.starts_with("xlink:") {
+            Some(Namespace::XLink)
+        } else if name.starts_with("xml:") {
+            Some(Namespace::Xml)
+        } else {
+            None
+        }
+    }
+}
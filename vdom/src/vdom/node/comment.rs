@@ -0,0 +1,218 @@
+use super::*;
+
+pub trait Comment<D>
+where
+    D: Driver,
+{
+    fn is_static(&self) -> bool;
+    fn get(&self) -> &str;
+    fn driver_store(&mut self) -> &mut D::CommentStore;
+}
+
+pub struct CommentStatic<D>
+where
+    D: Driver,
+{
+    comment: &'static str,
+    driver_store: D::CommentStore,
+}
+
+impl<D> CommentStatic<D>
+where
+    D: Driver,
+{
+    pub fn new(comment: &'static str) -> CommentStatic<D> {
+        CommentStatic {
+            comment,
+            driver_store: D::new_comment_store(),
+        }
+    }
+}
+
+impl<D> Comment<D> for CommentStatic<D>
+where
+    D: Driver,
+{
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn get(&self) -> &str {
+        self.comment
+    }
+
+    fn driver_store(&mut self) -> &mut D::CommentStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D> Node<D> for CommentStatic<D>
+where
+    D: Driver,
+{
+    const IS_STATIC: bool = true;
+
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_comment(*index, self)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        if ptr::eq(self.comment, ancestor.comment) {
+            mem::swap(self, ancestor);
+        } else {
+            differ.on_comment(*curr_index, *ancestor_index, self, ancestor)?;
+        }
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
+
+pub struct CommentDyn<D>
+where
+    D: Driver,
+{
+    comment: Cow<'static, str>,
+    driver_store: D::CommentStore,
+}
+
+impl<D> CommentDyn<D>
+where
+    D: Driver,
+{
+    pub fn new<C>(comment: C) -> CommentDyn<D>
+    where
+        C: Into<Cow<'static, str>>,
+    {
+        CommentDyn {
+            comment: comment.into(),
+            driver_store: D::new_comment_store(),
+        }
+    }
+}
+
+impl<D> Comment<D> for CommentDyn<D>
+where
+    D: Driver,
+{
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn get(&self) -> &str {
+        self.comment.as_ref()
+    }
+
+    fn driver_store(&mut self) -> &mut D::CommentStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D> Node<D> for CommentDyn<D>
+where
+    D: Driver,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_comment(*index, self)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_comment(*curr_index, *ancestor_index, self, ancestor)?;
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
+
+/// Places an anchor comment in front of a node, e.g. an `Option` or a component.
+///
+/// Nodes like these don't have any DOM footprint of their own when empty, the anchor marks where
+/// they start, which makes their position findable when hydrating or inserting.
+pub struct Anchored<D, N>
+where
+    D: Driver,
+{
+    anchor: CommentStatic<D>,
+    node: N,
+}
+
+impl<D, N> Anchored<D, N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    pub fn new(label: &'static str, node: N) -> Anchored<D, N> {
+        Anchored {
+            anchor: CommentStatic::new(label),
+            node,
+        }
+    }
+
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    pub fn node_mut(&mut self) -> &mut N {
+        &mut self.node
+    }
+}
+
+impl<D, N> Node<D> for Anchored<D, N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    const IS_STATIC: bool = N::IS_STATIC;
+
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.anchor.visit(index, visitor)?;
+        self.node.visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.anchor
+            .diff(curr_index, ancestor_index, &mut ancestor.anchor, differ)?;
+        self.node
+            .diff(curr_index, ancestor_index, &mut ancestor.node, differ)
+    }
+}
@@ -1,8 +1,15 @@
 use crate::{
     driver::{Driver, DriverCtx},
-    vdom::node::{Node, NodeDiffer, NodeVisitor},
+    vdom::{
+        attr::NodeRef,
+        node::{Node, NodeDiffer, NodeVisitor},
+    },
+};
+use futures::{
+    channel::mpsc,
+    task::{LocalSpawnExt, SpawnError},
+    Sink, Stream,
 };
-use futures::{channel::mpsc, Sink, Stream, StreamExt as _};
 use std::{
     cell::{Ref, RefCell, RefMut},
     future::Future,
@@ -11,7 +18,7 @@ use std::{
     mem,
     pin::Pin,
     rc::{Rc, Weak},
-    task,
+    task::{self, Waker},
 };
 
 pub trait Comp<D>
@@ -82,14 +89,12 @@ where
 
         let rendered = match &mut self.comp_rendered {
             NotRendered => {
-                let instance = self
+                let mut instance = self
                     .comp_ctx
                     .as_ref()
                     .expect("CompNode.comp_ctx is None")
                     .instance_mut();
-                let rendered = instance.comp.render(&instance.input);
-                self.comp_rendered =
-                    Rendered(instance.comp.clone(), instance.input.clone(), rendered);
+                self.comp_rendered = instance.render();
                 match &mut self.comp_rendered {
                     Rendered(_, _, rendered) => rendered,
                     _ => unreachable!(),
@@ -113,6 +118,13 @@ where
     {
         use self::CompNodeCompRendered::*;
 
+        if let Some(input) = self.input.take() {
+            self.comp_ctx
+                .as_ref()
+                .expect("CompNode.comp_ctx is None")
+                .instance_mut()
+                .set_input(input);
+        }
         let (ancestor_comp, ancestor_input, ancestor_rendered) = match &mut ancestor.comp_rendered {
             NotRendered => panic!("ancestor.comp_rendered is NotRendered"),
             Rendered(ancestor_comp, ancestor_input, ancestor_rendered) => {
@@ -128,18 +140,19 @@ where
         };
         let rendered = match &mut self.comp_rendered {
             NotRendered => {
-                let instance = self
+                let mut instance = self
                     .comp_ctx
                     .as_ref()
                     .expect("CompNode.comp_ctx is None")
                     .instance_mut();
-                if ancestor_comp == &instance.comp && ancestor_input == &instance.input {
+                if !instance.is_dirty
+                    && ancestor_comp == &instance.comp
+                    && ancestor_input == &instance.input
+                {
                     self.comp_rendered = mem::replace(&mut ancestor.comp_rendered, Taken);
                     return Ok(());
                 } else {
-                    let rendered = instance.comp.render(&instance.input);
-                    self.comp_rendered =
-                        Rendered(instance.comp.clone(), instance.input.clone(), rendered);
+                    self.comp_rendered = instance.render();
                     match &mut self.comp_rendered {
                         Rendered(_, _, rendered) => rendered,
                         _ => unreachable!(),
@@ -152,6 +165,47 @@ where
         rendered.diff(curr_index, ancestor_index, ancestor_rendered, differ)
     }
 
+    /// Whether the component requested a render, see `CompCtx::request_render`.
+    pub fn is_dirty(&self) -> bool {
+        match &self.comp_ctx {
+            Some(ctx) => ctx.instance().is_dirty,
+            None => false,
+        }
+    }
+
+    /// Renders the mounted component again and diffs the result against the previous render.
+    ///
+    /// Used by drivers to update the components that requested a render, without diffing the
+    /// whole tree.
+    pub fn refresh_rendered<ND>(
+        &mut self,
+        index: &mut usize,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        use self::CompNodeCompRendered::*;
+
+        let mut ancestor_rendered = match mem::replace(&mut self.comp_rendered, Taken) {
+            Rendered(_, _, rendered) => rendered,
+            NotRendered => panic!("comp_rendered is NotRendered"),
+            Taken => panic!("comp_rendered is Taken"),
+        };
+        self.comp_rendered = self
+            .comp_ctx
+            .as_ref()
+            .expect("CompNode.comp_ctx is None")
+            .instance_mut()
+            .render();
+        match &mut self.comp_rendered {
+            Rendered(_, _, rendered) => {
+                rendered.diff(index, &mut index.clone(), &mut ancestor_rendered, differ)
+            }
+            _ => unreachable!(),
+        }
+    }
+
     pub fn driver_store(&mut self) -> &mut D::CompStore {
         &mut self.driver_store
     }
@@ -191,9 +245,56 @@ where
     pub comp: C,
     pub input: C::Input,
     driver_ctx: DriverCtx<D>,
+    /// Set by `CompCtx::request_render`, until the component is rendered again.
+    is_dirty: bool,
+    /// Bumped whenever the input changes, see `LifetimeReceiver`.
+    input_version: u64,
+    /// Tasks to wake when the input changes or the instance is dropped.
+    input_wakers: Vec<Waker>,
     phantom: PhantomData<D>,
 }
 
+impl<D, C> CompInstance<D, C>
+where
+    D: Driver,
+    C: Comp<D>,
+{
+    fn render(&mut self) -> CompNodeCompRendered<D, C> {
+        self.is_dirty = false;
+        let rendered = self.comp.render(&self.input);
+        CompNodeCompRendered::Rendered(self.comp.clone(), self.input.clone(), rendered)
+    }
+
+    fn set_input(&mut self, input: C::Input) {
+        if input != self.input {
+            self.input = input;
+            self.input_version += 1;
+            for waker in mem::take(&mut self.input_wakers) {
+                waker.wake();
+            }
+        }
+    }
+
+    fn register_input_waker(&mut self, waker: &Waker) {
+        if !self.input_wakers.iter().any(|w| w.will_wake(waker)) {
+            self.input_wakers.push(waker.clone());
+        }
+    }
+}
+
+impl<D, C> Drop for CompInstance<D, C>
+where
+    D: Driver,
+    C: Comp<D>,
+{
+    fn drop(&mut self) {
+        // lets the futures bound to the instance notice that it's gone
+        for waker in mem::take(&mut self.input_wakers) {
+            waker.wake();
+        }
+    }
+}
+
 pub struct StrongCompCtx<D, C>
 where
     D: Driver,
@@ -211,11 +312,21 @@ where
         let ctx = StrongCompCtx {
             instance: Rc::new(RefCell::new(None)),
         };
-        let comp = C::new(&input, ctx.downgrade());
+        // the instance doesn't exist yet, the context gets the driver directly
+        let comp = C::new(
+            &input,
+            CompCtx {
+                instance: Rc::downgrade(&ctx.instance),
+                driver_ctx: driver_ctx.clone(),
+            },
+        );
         *ctx.instance.borrow_mut() = Some(CompInstance {
             comp,
             input,
             driver_ctx,
+            is_dirty: false,
+            input_version: 0,
+            input_wakers: Vec::new(),
             phantom: PhantomData,
         });
         ctx
@@ -224,6 +335,7 @@ where
     pub fn downgrade(&self) -> CompCtx<D, C> {
         CompCtx {
             instance: Rc::downgrade(&self.instance),
+            driver_ctx: self.instance().driver_ctx.clone(),
         }
     }
 
@@ -281,6 +393,7 @@ where
     C: Comp<D>,
 {
     instance: Weak<RefCell<Option<CompInstance<D, C>>>>,
+    driver_ctx: DriverCtx<D>,
 }
 
 impl<D, C> CompCtx<D, C>
@@ -288,7 +401,7 @@ where
     D: Driver,
     C: Comp<D>,
 {
-    pub fn build_stream<F, T, R>(&self, f: F) -> mpsc::UnboundedSender<T>
+    pub fn build_stream<F, T, R>(&self, f: F) -> Result<mpsc::UnboundedSender<T>, SpawnError>
     where
         F: FnOnce(mpsc::UnboundedReceiver<T>) -> R,
         R: Future<Output = ()> + 'static,
@@ -297,19 +410,64 @@ where
 
         let fut = f(receiver);
 
-        // let id =
-        self.with_instance(|instance| {
-            instance.driver_ctx.with_mut(|drv| {
-                drv.spawn(fut);
-            });
-            // instance.driver_ctx.next_id()
-        })
-        .unwrap();
+        // let id = self.driver_ctx.next_id();
+        self.spawn(fut)?;
 
-        sender
+        Ok(sender)
         // Sender { sender, id }
     }
 
+    /// Marks the component as dirty and asks the driver to render it again.
+    ///
+    /// Does nothing if the component is gone. Must not be called while the component renders.
+    pub fn request_render(&self) {
+        self.with_instance_mut(|instance| {
+            instance.is_dirty = true;
+            instance.driver_ctx.with_mut(|drv| drv.request_render());
+        });
+    }
+
+    /// Renders the component again for every item of `stream`, e.g. the changes of a shared cell
+    /// the component depends on.
+    ///
+    /// The subscription ends with the stream, or when the component is dropped.
+    pub fn subscribe<S>(&self, stream: S) -> Result<(), SpawnError>
+    where
+        S: Stream + 'static,
+        D: 'static,
+        C: 'static,
+    {
+        let subscription = Subscription {
+            stream: Box::pin(stream),
+            ctx: self.clone(),
+        };
+        self.spawn(subscription)
+    }
+
+    /// Spawns `fut` on the driver, e.g. a `ForwardWith`; does nothing if the component is gone.
+    ///
+    /// Can already be called from `Comp::new`. Fails if the executor of the driver can't spawn,
+    /// e.g. when it's shut down.
+    pub fn spawn<F>(&self, fut: F) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        if self.instance.upgrade().is_none() {
+            return Ok(());
+        }
+        self.driver_ctx.with_mut(|drv| drv.spawn_local(fut))
+    }
+
+    /// Creates a handle to be put on one of the rendered tags with `ref=(node_ref)`.
+    pub fn node_ref(&self) -> NodeRef<D> {
+        NodeRef::new()
+    }
+
+    /// The element behind `node_ref`, `None` if it isn't mounted or the component is gone.
+    pub fn element(&self, node_ref: &NodeRef<D>) -> Option<D::Element> {
+        self.instance.upgrade().and_then(|_| node_ref.get())
+    }
+
     pub fn with_instance<F, R>(&self, f: F) -> Option<R>
     where
         F: FnOnce(&CompInstance<D, C>) -> R,
@@ -345,6 +503,38 @@ where
     fn clone(&self) -> Self {
         CompCtx {
             instance: self.instance.clone(),
+            driver_ctx: self.driver_ctx.clone(),
+        }
+    }
+}
+
+struct Subscription<S, D, C>
+where
+    D: Driver,
+    C: Comp<D>,
+{
+    stream: Pin<Box<S>>,
+    ctx: CompCtx<D, C>,
+}
+
+impl<S, D, C> Future for Subscription<S, D, C>
+where
+    S: Stream,
+    D: Driver,
+    C: Comp<D>,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<()> {
+        loop {
+            if self.ctx.instance.upgrade().is_none() {
+                return task::Poll::Ready(());
+            }
+            match self.stream.as_mut().poll_next(cx) {
+                task::Poll::Ready(Some(_)) => self.ctx.request_render(),
+                task::Poll::Ready(None) => return task::Poll::Ready(()),
+                task::Poll::Pending => return task::Poll::Pending,
+            }
         }
     }
 }
@@ -372,105 +562,402 @@ where
 //     }
 // }
 
-// pub struct ForwardWith<S, F, R, D, C>
-// where
-//     S: Stream,
-//     F: FnMut(&CompInstance<D, C>) -> &R,
-//     R: Sink<S::Item>,
-//     D: Driver,
-//     C: Comp<D>,
-// {
-//     stream: S,
-//     sink: R,
-//     f: F,
-//     ctx: CompCtx<D, C>,
-// }
+/// Forwards the items of a stream into a sink selected from the component instance, see
+/// `StreamExt::forward_with`.
+pub struct ForwardWith<S, F, R, D, C>
+where
+    S: Stream,
+    D: Driver,
+    C: Comp<D>,
+{
+    stream: Pin<Box<S>>,
+    /// Selected on the first poll, as the instance might still be under construction before.
+    sink: Option<R>,
+    /// Selected after an input change, replaces `sink` once it's flushed.
+    next_sink: Option<R>,
+    buffered: Option<S::Item>,
+    f: F,
+    ctx: CompCtx<D, C>,
+    input_version: u64,
+}
 
-// // impl ForwardWith<S, F, R, D, C>
-// // where
-// //     S: Stream,
-// //     F: FnMut(&'_ mut CompInstance<D, C>) -> &'_ mut R,
-// //     R: Sink<S::Item>,
-// //     D: Driver,
-// //     C: Comp<D>,
-// // {
-// // }
-
-// impl<S, F, R, D, C> Future for ForwardWith<S, F, R, D, C>
-// where
-//     S: Stream,
-//     F: FnMut(&'_ mut CompInstance<D, C>) -> &'_ R,
-//     R: Sink<S::Item>,
-//     D: Driver,
-//     C: Comp<D>,
-// {
-//     type Output = ();
-
-//     fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
-//         // loop {
-//         //     match self.as_mut().stream().poll_next(cx) {
-//         //         Poll::Ready(Some(Ok(item))) => ready!(self.as_mut().try_start_send(cx, item))?,
-//         //         Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
-//         //         Poll::Ready(None) => {
-//         //             ready!(self
-//         //                 .as_mut()
-//         //                 .sink()
-//         //                 .as_pin_mut()
-//         //                 .expect(INVALID_POLL)
-//         //                 .poll_close(cx))?;
-//         //             self.as_mut().sink().set(None);
-//         //             return Poll::Ready(Ok(()));
-//         //         }
-//         //         Poll::Pending => {
-//         //             ready!(self
-//         //                 .as_mut()
-//         //                 .sink()
-//         //                 .as_pin_mut()
-//         //                 .expect(INVALID_POLL)
-//         //                 .poll_flush(cx))?;
-//         //             return Poll::Pending;
-//         //         }
-//         //     }
-//         // }
-
-//         unimplemented!()
-//     }
-// }
+// none of the fields are pinned, the stream is boxed
+impl<S, F, R, D, C> Unpin for ForwardWith<S, F, R, D, C>
+where
+    S: Stream,
+    D: Driver,
+    C: Comp<D>,
+{
+}
 
-// impl<S, F, R, D, C> LifetimeReceiver<D, C> for ForwardWith<S, F, R, D, C>
-// where
-//     S: Stream,
-//     F: FnMut(&'_ mut CompInstance<D, C>) -> & R,
-//     R: Sink<S::Item> + Eq,
-//     D: Driver,
-//     C: Comp<D>,
-// {
-//     fn on_input_changed(&mut self, instance: &mut CompInstance<D, C>) {
-//         let new_sink = self.f(instance);
-
-//         if new_sink != self.sink {
-//             self.sink = new_sink;
-//         }
-//     }
-// }
+impl<S, F, R, D, C> ForwardWith<S, F, R, D, C>
+where
+    S: Stream,
+    F: FnMut(&CompInstance<D, C>) -> &R,
+    R: Sink<S::Item> + Clone + Eq + Unpin,
+    D: Driver,
+    C: Comp<D>,
+{
+    /// Tracks input changes, returns `false` if the component is gone.
+    fn poll_instance(&mut self, cx: &mut task::Context<'_>) -> bool {
+        let ctx = self.ctx.clone();
+        ctx.with_instance_mut(|instance| {
+            instance.register_input_waker(cx.waker());
+            if self.sink.is_none() || instance.input_version != self.input_version {
+                self.input_version = instance.input_version;
+                self.on_input_changed(instance);
+            }
+        })
+        .is_some()
+    }
+}
 
-// pub trait StreamExt: Stream {
-//     fn forward_with<F, R, D, C>(self, ctx: CompCtx<D, C>, f: F) -> ForwardWith<Self, F, R, D, C>
-//     where
-//         Self: Sized,
-//         F: FnMut(&'_ mut CompInstance<D, C>) -> &'_ mut R,
-//         R: Sink<Self::Item>,
-//         D: Driver,
-//         C: Comp<D>,
-//     {
-//         unimplemented!()
-//     }
-// }
+impl<S, F, R, D, C> Future for ForwardWith<S, F, R, D, C>
+where
+    S: Stream,
+    F: FnMut(&CompInstance<D, C>) -> &R,
+    R: Sink<S::Item> + Clone + Eq + Unpin,
+    D: Driver,
+    C: Comp<D>,
+{
+    type Output = ();
 
-// pub trait LifetimeReceiver<D, C>
-// where
-//     D: Driver,
-//     C: Comp<D>,
-// {
-//     fn on_input_changed(&mut self, _instance: &mut CompInstance<D, C>) {}
-// }
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<()> {
+        use std::task::Poll;
+
+        let this = &mut *self;
+        if !this.poll_instance(cx) {
+            return Poll::Ready(());
+        }
+        if this.next_sink.is_some() {
+            // the items sent so far still go into the old sink
+            match Pin::new(this.sink.as_mut().unwrap()).poll_flush(cx) {
+                Poll::Ready(Ok(())) => this.sink = this.next_sink.take(),
+                Poll::Ready(Err(_)) => return Poll::Ready(()),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        loop {
+            if this.buffered.is_some() {
+                match Pin::new(this.sink.as_mut().unwrap()).poll_ready(cx) {
+                    Poll::Ready(Ok(())) => {
+                        let item = this.buffered.take().unwrap();
+                        if Pin::new(this.sink.as_mut().unwrap())
+                            .start_send(item)
+                            .is_err()
+                        {
+                            return Poll::Ready(());
+                        }
+                    }
+                    Poll::Ready(Err(_)) => return Poll::Ready(()),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => this.buffered = Some(item),
+                Poll::Ready(None) => {
+                    return match Pin::new(this.sink.as_mut().unwrap()).poll_close(cx) {
+                        Poll::Pending => Poll::Pending,
+                        Poll::Ready(_) => Poll::Ready(()),
+                    };
+                }
+                Poll::Pending => {
+                    return match Pin::new(this.sink.as_mut().unwrap()).poll_flush(cx) {
+                        Poll::Ready(Err(_)) => Poll::Ready(()),
+                        _ => Poll::Pending,
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<S, F, R, D, C> LifetimeReceiver<D, C> for ForwardWith<S, F, R, D, C>
+where
+    S: Stream,
+    F: FnMut(&CompInstance<D, C>) -> &R,
+    R: Sink<S::Item> + Clone + Eq + Unpin,
+    D: Driver,
+    C: Comp<D>,
+{
+    fn on_input_changed(&mut self, instance: &CompInstance<D, C>) {
+        let new_sink = (self.f)(instance);
+        if self.sink.is_none() {
+            self.sink = Some(new_sink.clone());
+        } else if self.sink.as_ref() == Some(new_sink) {
+            self.next_sink = None;
+        } else {
+            self.next_sink = Some(new_sink.clone());
+        }
+    }
+}
+
+pub trait StreamExt: Stream {
+    /// Forwards the items into the sink `f` selects from the component instance, until the stream
+    /// ends, the sink fails or the component is dropped.
+    ///
+    /// The sink is selected again whenever the input of the component changes, and replaces the
+    /// previous one once that is flushed. The returned future has to be spawned, e.g. with
+    /// `CompCtx::spawn`.
+    fn forward_with<F, R, D, C>(self, ctx: &CompCtx<D, C>, f: F) -> ForwardWith<Self, F, R, D, C>
+    where
+        Self: Sized,
+        F: FnMut(&CompInstance<D, C>) -> &R,
+        R: Sink<Self::Item> + Clone + Eq + Unpin,
+        D: Driver,
+        C: Comp<D>,
+    {
+        ForwardWith {
+            stream: Box::pin(self),
+            sink: None,
+            next_sink: None,
+            buffered: None,
+            f,
+            ctx: ctx.clone(),
+            input_version: 0,
+        }
+    }
+}
+
+impl<S> StreamExt for S where S: Stream {}
+
+/// Futures bound to a component, which are notified about its changes.
+pub trait LifetimeReceiver<D, C>
+where
+    D: Driver,
+    C: Comp<D>,
+{
+    fn on_input_changed(&mut self, _instance: &CompInstance<D, C>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{
+        channel::mpsc,
+        executor::{LocalPool, LocalSpawner},
+        future::LocalFutureObj,
+        task::LocalSpawn,
+    };
+    use sink_cell::SinkCell;
+    use std::task::Poll;
+
+    struct TestDriver {
+        spawner: LocalSpawner,
+    }
+
+    impl Driver for TestDriver {
+        type AttrStore = ();
+        type TagStore = ();
+        type TextStore = ();
+        type CommentStore = ();
+        type CompStore = ();
+        type PortalStore = ();
+        type RawHtmlStore = ();
+        type Element = ();
+        type Err = ();
+
+        fn new_attr_store() {}
+        fn new_tag_store() {}
+        fn new_text_store() {}
+        fn new_comment_store() {}
+        fn new_comp_store() {}
+        fn new_portal_store() {}
+        fn new_raw_html_store() {}
+
+        fn request_render(&mut self) {}
+
+        fn error_handler(&self) -> Rc<dyn Fn(())> {
+            Rc::new(|_| panic!("unexpected error"))
+        }
+    }
+
+    impl LocalSpawn for TestDriver {
+        fn spawn_local_obj(
+            &mut self,
+            future: LocalFutureObj<'static, ()>,
+        ) -> Result<(), SpawnError> {
+            self.spawner.spawn_local_obj(future)
+        }
+    }
+
+    /// Forwards everything sent through its sender into the sink it gets as input.
+    #[derive(Clone)]
+    struct Forwarder<S> {
+        sender: Rc<mpsc::UnboundedSender<u32>>,
+        _sink: PhantomData<S>,
+    }
+
+    impl<S> PartialEq for Forwarder<S> {
+        fn eq(&self, other: &Self) -> bool {
+            Rc::ptr_eq(&self.sender, &other.sender)
+        }
+    }
+
+    impl<S> Eq for Forwarder<S> {}
+
+    impl<S> Comp<TestDriver> for Forwarder<S>
+    where
+        S: Sink<u32> + Clone + Eq + Unpin + 'static,
+    {
+        type Input = S;
+        type Rendered = ();
+
+        fn new(_input: &S, ctx: CompCtx<TestDriver, Self>) -> Forwarder<S> {
+            let (sender, receiver) = mpsc::unbounded();
+            ctx.spawn(receiver.forward_with(&ctx, |instance| &instance.input))
+                .expect("failed to spawn the forwarding");
+            Forwarder {
+                sender: Rc::new(sender),
+                _sink: PhantomData,
+            }
+        }
+
+        fn render(&self, _input: &S) {}
+    }
+
+    fn mount<S>(pool: &LocalPool, sink: &S) -> StrongCompCtx<TestDriver, Forwarder<S>>
+    where
+        S: Sink<u32> + Clone + Eq + Unpin + 'static,
+    {
+        let driver_ctx = DriverCtx::new(TestDriver {
+            spawner: pool.spawner(),
+        });
+        StrongCompCtx::new(driver_ctx, sink.clone())
+    }
+
+    fn send<S>(ctx: &StrongCompCtx<TestDriver, Forwarder<S>>, value: u32)
+    where
+        S: Sink<u32> + Clone + Eq + Unpin + 'static,
+    {
+        ctx.instance().comp.sender.unbounded_send(value).unwrap();
+    }
+
+    #[derive(Default)]
+    struct BufferState {
+        buffered: Vec<u32>,
+        flushed: Vec<u32>,
+        is_blocked: bool,
+        waker: Option<Waker>,
+    }
+
+    /// Buffers the items until it's flushed, which doesn't complete while it's blocked.
+    #[derive(Clone, Default)]
+    struct BufferSink {
+        state: Rc<RefCell<BufferState>>,
+    }
+
+    impl BufferSink {
+        fn set_blocked(&self, is_blocked: bool) {
+            let mut state = self.state.borrow_mut();
+            state.is_blocked = is_blocked;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+
+        fn flushed(&self) -> Vec<u32> {
+            self.state.borrow().flushed.clone()
+        }
+    }
+
+    impl PartialEq for BufferSink {
+        fn eq(&self, other: &Self) -> bool {
+            Rc::ptr_eq(&self.state, &other.state)
+        }
+    }
+
+    impl Eq for BufferSink {}
+
+    impl Sink<u32> for BufferSink {
+        type Error = ();
+
+        fn poll_ready(self: Pin<&mut Self>, _cx: &mut task::Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: u32) -> Result<(), ()> {
+            self.state.borrow_mut().buffered.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Result<(), ()>> {
+            let state = &mut *self.state.borrow_mut();
+            if state.is_blocked {
+                state.waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            state.flushed.append(&mut state.buffered);
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Result<(), ()>> {
+            self.poll_flush(cx)
+        }
+    }
+
+    #[test]
+    fn forwards_into_sink() {
+        let mut pool = LocalPool::new();
+        let cell = SinkCell::new(0);
+        let ctx = mount(&pool, &cell);
+
+        send(&ctx, 1);
+        send(&ctx, 2);
+        pool.run_until_stalled();
+        assert_eq!(cell.get(), 2);
+    }
+
+    #[test]
+    fn reselects_sink_on_input_change() {
+        let mut pool = LocalPool::new();
+        let first = SinkCell::new(0);
+        let second = SinkCell::new(0);
+        let ctx = mount(&pool, &first);
+
+        send(&ctx, 1);
+        pool.run_until_stalled();
+        ctx.instance_mut().set_input(second.clone());
+        send(&ctx, 2);
+        pool.run_until_stalled();
+        assert_eq!(first.get(), 1);
+        assert_eq!(second.get(), 2);
+    }
+
+    #[test]
+    fn stops_when_comp_is_dropped() {
+        let mut pool = LocalPool::new();
+        let cell = SinkCell::new(0);
+        let ctx = mount(&pool, &cell);
+        let sender = ctx.instance().comp.sender.clone();
+
+        pool.run_until_stalled();
+        drop(ctx);
+        // returns only once the spawned future is done
+        pool.run();
+        assert!(sender.unbounded_send(1).is_err());
+        assert_eq!(cell.get(), 0);
+    }
+
+    #[test]
+    fn flushes_previous_sink_before_switching() {
+        let mut pool = LocalPool::new();
+        let first = BufferSink::default();
+        let second = BufferSink::default();
+        first.set_blocked(true);
+        let ctx = mount(&pool, &first);
+
+        send(&ctx, 1);
+        pool.run_until_stalled();
+        ctx.instance_mut().set_input(second.clone());
+        send(&ctx, 2);
+        pool.run_until_stalled();
+        assert_eq!(first.flushed(), []);
+        assert!(second.state.borrow().buffered.is_empty());
+
+        first.set_blocked(false);
+        pool.run_until_stalled();
+        assert_eq!(first.flushed(), [1]);
+        assert_eq!(second.flushed(), [2]);
+    }
+}
@@ -1,13 +1,27 @@
+mod comment;
 mod comp;
+mod portal;
+mod raw_html;
+mod signal_text;
+mod suspense;
 mod tag;
 mod text;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt, mem, ptr};
 
+pub use self::comment::*;
 pub use self::comp::*;
+pub use self::portal::*;
+pub use self::raw_html::*;
+pub use self::signal_text::*;
+pub use self::suspense::*;
 pub use self::tag::*;
 pub use self::text::*;
-use super::attr::{AttrDiffer, AttrList, AttrVisitor};
+use super::{
+    attr::{AttrDiffer, AttrList, AttrVisitor},
+    namespace::Namespace,
+    signal::ReadSignal,
+};
 use crate::driver::Driver;
 
 pub trait NodeVisitor<D>
@@ -24,9 +38,37 @@ where
     where
         T: Text<D>;
 
+    fn on_comment<C>(&mut self, index: usize, comment: &mut C) -> Result<(), Self::Err>
+    where
+        C: Comment<D>;
+
     fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Self::Err>
     where
         C: Comp<D>;
+
+    fn on_portal<N>(&mut self, portal: &mut Portal<D, N>) -> Result<(), Self::Err>
+    where
+        N: Node<D>;
+
+    fn on_raw_html(&mut self, index: usize, raw_html: &mut RawHtml<D>) -> Result<(), Self::Err>;
+
+    fn on_signal_text<S>(
+        &mut self,
+        index: usize,
+        text: &mut SignalText<D, S>,
+    ) -> Result<(), Self::Err>
+    where
+        S: ReadSignal,
+        S::Value: fmt::Display;
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<D, N, Fb>,
+    ) -> Result<(), Self::Err>
+    where
+        N: Node<D> + 'static,
+        Fb: Node<D> + 'static;
 }
 
 pub trait NodeDiffer<D>
@@ -47,6 +89,10 @@ where
     where
         N: Node<D>;
 
+    /// Called for tags that may have changed.
+    ///
+    /// The tag names of `curr` and `ancestor` can differ for tags named at runtime, in which case
+    /// the driver has to replace the element, keeping the children.
     fn on_tag<T>(
         &mut self,
         curr_index: usize,
@@ -67,6 +113,16 @@ where
     where
         T: Text<D>;
 
+    fn on_comment<C>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut C,
+        ancestor: &mut C,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comment<D>;
+
     fn on_comp<C>(
         &mut self,
         curr_index: &mut usize,
@@ -76,40 +132,64 @@ where
     ) -> Result<(), Self::Err>
     where
         C: Comp<D>;
-}
 
-pub trait Node<D>
-where
-    D: Driver,
-{
-    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    /// Called for portals, whose target element may have changed.
+    fn on_portal<N>(
+        &mut self,
+        curr: &mut Portal<D, N>,
+        ancestor: &mut Portal<D, N>,
+    ) -> Result<(), Self::Err>
     where
-        NV: NodeVisitor<D>;
+        N: Node<D>;
 
-    fn diff<ND>(
+    /// Called for raw HTML nodes, whose markup or wrapper element may have changed.
+    fn on_raw_html(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut RawHtml<D>,
+        ancestor: &mut RawHtml<D>,
+    ) -> Result<(), Self::Err>;
+
+    /// Called for texts bound to a signal, which may be bound to another signal now.
+    fn on_signal_text<S>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut SignalText<D, S>,
+        ancestor: &mut SignalText<D, S>,
+    ) -> Result<(), Self::Err>
+    where
+        S: ReadSignal,
+        S::Value: fmt::Display;
+
+    fn on_suspense<N, Fb>(
         &mut self,
         curr_index: &mut usize,
         ancestor_index: &mut usize,
-        ancestor: &mut Self,
-        differ: &mut ND,
-    ) -> Result<(), ND::Err>
+        curr: &mut Suspense<D, N, Fb>,
+        ancestor: &mut Suspense<D, N, Fb>,
+    ) -> Result<(), Self::Err>
     where
-        ND: NodeDiffer<D>;
+        N: Node<D> + 'static,
+        Fb: Node<D> + 'static;
 }
 
-impl<D, L1, L2> Node<D> for (L1, L2)
+pub trait Node<D>
 where
     D: Driver,
-    L1: Node<D>,
-    L2: Node<D>,
 {
+    /// Whether the node and its whole subtree are made of `&'static` values only.
+    ///
+    /// Static subtrees carrying the same `html!` template aren't diffed at all, instead the
+    /// ancestor, along with all its driver stores, is moved over wholesale. Other static nodes are
+    /// only moved over if their values are the very same `&'static str`, as they could still be
+    /// picked at runtime.
+    const IS_STATIC: bool = false;
+
     fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
     where
-        NV: NodeVisitor<D>,
-    {
-        self.0.visit(index, visitor)?;
-        self.1.visit(index, visitor)
-    }
+        NV: NodeVisitor<D>;
 
     fn diff<ND>(
         &mut self,
@@ -119,19 +199,73 @@ where
         differ: &mut ND,
     ) -> Result<(), ND::Err>
     where
-        ND: NodeDiffer<D>,
+        ND: NodeDiffer<D>;
+
+    /// Places an anchor comment in front of the node, see `Anchored`.
+    fn anchored(self, label: &'static str) -> Anchored<D, Self>
+    where
+        Self: Sized,
     {
-        self.0
-            .diff(curr_index, ancestor_index, &mut ancestor.0, differ)?;
-        self.1
-            .diff(curr_index, ancestor_index, &mut ancestor.1, differ)
+        Anchored::new(label, self)
     }
 }
 
+macro_rules! impl_node_tuple {
+    ($($ty:ident: $idx:tt),*) => {
+        impl<D, $($ty),*> Node<D> for ($($ty,)*)
+        where
+            D: Driver,
+            $($ty: Node<D>,)*
+        {
+            const IS_STATIC: bool = $($ty::IS_STATIC)&&*;
+
+            fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+            where
+                NV: NodeVisitor<D>,
+            {
+                $(self.$idx.visit(index, visitor)?;)*
+                Ok(())
+            }
+
+            fn diff<ND>(
+                &mut self,
+                curr_index: &mut usize,
+                ancestor_index: &mut usize,
+                ancestor: &mut Self,
+                differ: &mut ND,
+            ) -> Result<(), ND::Err>
+            where
+                ND: NodeDiffer<D>,
+            {
+                $(self.$idx.diff(curr_index, ancestor_index, &mut ancestor.$idx, differ)?;)*
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_node_tuple!(L1: 0, L2: 1);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12, L14: 13);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12, L14: 13, L15: 14);
+impl_node_tuple!(L1: 0, L2: 1, L3: 2, L4: 3, L5: 4, L6: 5, L7: 6, L8: 7, L9: 8, L10: 9, L11: 10, L12: 11, L13: 12, L14: 13, L15: 14, L16: 15);
+
 impl<D> Node<D> for ()
 where
     D: Driver,
 {
+    const IS_STATIC: bool = true;
+
     fn visit<NV>(&mut self, _index: &mut usize, _visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -229,3 +363,24 @@ where
         TextDyn::new(self)
     }
 }
+
+macro_rules! impl_into_node_display {
+    ($($ty:ty),*) => {
+        $(
+            impl<D> IntoNode<D> for $ty
+            where
+                D: Driver,
+            {
+                type Node = DisplayText<D, $ty>;
+
+                fn into_node(self) -> Self::Node {
+                    DisplayText::new(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_node_display!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, bool
+);
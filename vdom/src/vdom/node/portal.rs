@@ -0,0 +1,119 @@
+use super::*;
+
+/// The element a `Portal` mounts its children into.
+pub enum PortalTarget<D>
+where
+    D: Driver,
+{
+    Element(D::Element),
+    /// The element with that `id`, looked up by the driver when the portal is mounted.
+    Id(Cow<'static, str>),
+}
+
+impl<D> Clone for PortalTarget<D>
+where
+    D: Driver,
+{
+    fn clone(&self) -> Self {
+        match self {
+            PortalTarget::Element(elem) => PortalTarget::Element(elem.clone()),
+            PortalTarget::Id(id) => PortalTarget::Id(id.clone()),
+        }
+    }
+}
+
+impl<D> PartialEq for PortalTarget<D>
+where
+    D: Driver,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PortalTarget::Element(a), PortalTarget::Element(b)) => a == b,
+            (PortalTarget::Id(a), PortalTarget::Id(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Renders its children into another element, e.g. modals into `document.body`.
+///
+/// The children are still diffed and removed along with the tree the portal is part of, but don't
+/// take up any index among the siblings of the portal.
+pub struct Portal<D, N>
+where
+    D: Driver,
+{
+    target: PortalTarget<D>,
+    children: N,
+    driver_store: D::PortalStore,
+}
+
+impl<D, N> Portal<D, N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    pub fn new(target: PortalTarget<D>, children: N) -> Portal<D, N> {
+        Portal {
+            target,
+            children,
+            driver_store: D::new_portal_store(),
+        }
+    }
+
+    pub fn with_id<I>(id: I, children: N) -> Portal<D, N>
+    where
+        I: Into<Cow<'static, str>>,
+    {
+        Portal::new(PortalTarget::Id(id.into()), children)
+    }
+
+    pub fn target(&self) -> &PortalTarget<D> {
+        &self.target
+    }
+
+    pub fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.children.visit(&mut 0, visitor)
+    }
+
+    pub fn diff_children<ND>(&mut self, ancestor: &mut Self, differ: &mut ND) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.children
+            .diff(&mut 0, &mut 0, &mut ancestor.children, differ)
+    }
+
+    pub fn driver_store(&mut self) -> &mut D::PortalStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D, N> Node<D> for Portal<D, N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    fn visit<NV>(&mut self, _index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_portal(self)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        _curr_index: &mut usize,
+        _ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_portal(self, ancestor)
+    }
+}
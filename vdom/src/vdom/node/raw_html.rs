@@ -0,0 +1,87 @@
+use super::*;
+use crate::vdom::sanitize::Sanitizer;
+
+/// Pre-rendered markup (e.g. the output of a markdown renderer), placed in a wrapper element.
+///
+/// The markup is inserted as is, untrusted markup has to be passed through a `Sanitizer` first,
+/// see `RawHtml::sanitized`.
+pub struct RawHtml<D>
+where
+    D: Driver,
+{
+    wrapper: &'static str,
+    html: Cow<'static, str>,
+    driver_store: D::RawHtmlStore,
+}
+
+impl<D> RawHtml<D>
+where
+    D: Driver,
+{
+    /// Wraps the markup in a `div`.
+    pub fn new<H>(html: H) -> RawHtml<D>
+    where
+        H: Into<Cow<'static, str>>,
+    {
+        RawHtml::with_wrapper("div", html)
+    }
+
+    pub fn with_wrapper<H>(wrapper: &'static str, html: H) -> RawHtml<D>
+    where
+        H: Into<Cow<'static, str>>,
+    {
+        RawHtml {
+            wrapper,
+            html: html.into(),
+            driver_store: D::new_raw_html_store(),
+        }
+    }
+
+    /// Strips everything that isn't allowed by the default `Sanitizer` from the markup.
+    pub fn sanitized(html: &str) -> RawHtml<D> {
+        RawHtml::new(Sanitizer::default().sanitize(html))
+    }
+
+    /// The tag name of the wrapper element.
+    pub fn wrapper(&self) -> &'static str {
+        self.wrapper
+    }
+
+    pub fn html(&self) -> &str {
+        &self.html
+    }
+
+    pub fn driver_store(&mut self) -> &mut D::RawHtmlStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D> Node<D> for RawHtml<D>
+where
+    D: Driver,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_raw_html(*index, self)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_raw_html(*curr_index, *ancestor_index, self, ancestor)?;
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+use crate::vdom::signal::{ReadSignal, Subscription};
+use std::fmt::Display;
+
+/// A text bound to a signal, patched by the driver whenever the signal changes.
+///
+/// The component rendering the text isn't rendered again; diffing it against a text bound to the
+/// same signal keeps the binding as is.
+pub struct SignalText<D, S>
+where
+    D: Driver,
+{
+    signal: S,
+    /// The text at the time the node was created, used for mounting.
+    text: String,
+    binding: Option<Subscription>,
+    driver_store: D::TextStore,
+}
+
+impl<D, S> SignalText<D, S>
+where
+    D: Driver,
+    S: ReadSignal,
+    S::Value: Display,
+{
+    pub fn new(signal: &S) -> SignalText<D, S> {
+        SignalText {
+            signal: signal.clone(),
+            text: signal.with(ToString::to_string),
+            binding: None,
+            driver_store: D::new_text_store(),
+        }
+    }
+
+    pub fn signal(&self) -> &S {
+        &self.signal
+    }
+
+    /// Called by the driver once the text is mounted, `patch` gets the new text on every change.
+    ///
+    /// The binding is dropped along with the node.
+    pub fn bind<F>(&mut self, patch: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        self.binding = Some(
+            self.signal
+                .subscribe(move |value| patch(&value.to_string())),
+        );
+    }
+
+    /// Takes over the binding of `ancestor` if both are bound to the same signal.
+    ///
+    /// Returns `false` if they aren't, in which case the driver has to patch and bind the text.
+    pub fn adopt_binding(&mut self, ancestor: &mut Self) -> bool {
+        if self.signal.ptr_eq(&ancestor.signal) {
+            self.binding = ancestor.binding.take();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<D, S> Text<D> for SignalText<D, S>
+where
+    D: Driver,
+    S: ReadSignal,
+    S::Value: Display,
+{
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn get(&self) -> &str {
+        &self.text
+    }
+
+    fn driver_store(&mut self) -> &mut D::TextStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D, S> Node<D> for SignalText<D, S>
+where
+    D: Driver,
+    S: ReadSignal,
+    S::Value: Display,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_signal_text(*index, self)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_signal_text(*curr_index, *ancestor_index, self, ancestor)?;
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,395 @@
+use super::*;
+use crate::driver::DriverCtx;
+use futures::{Future, FutureExt};
+use std::{
+    cell::RefCell,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    pin::Pin,
+    rc::{Rc, Weak},
+};
+
+pub enum SuspenseContent<N, Fb> {
+    Pending(Fb),
+    Resolved(N),
+}
+
+struct SuspenseState<D, N, Fb>
+where
+    D: Driver,
+{
+    anchor: CommentStatic<D>,
+    content: SuspenseContent<N, Fb>,
+}
+
+/// Renders `fallback` until `future` resolves, then swaps in the node it resolved to.
+///
+/// The future is spawned by the driver when the suspense is mounted. Later renders keep the
+/// mounted suspense, pending or resolved, and drop their futures, unless their key differs from
+/// the one of the previous render, see `with_key`. An anchor comment marks the position of the
+/// content, so the driver can find it again once the future is done.
+pub struct Suspense<D, N, Fb>
+where
+    D: Driver,
+{
+    key: Option<u64>,
+    future: Option<Pin<Box<dyn Future<Output = N>>>>,
+    state: Rc<RefCell<SuspenseState<D, N, Fb>>>,
+}
+
+impl<D, N, Fb> Suspense<D, N, Fb>
+where
+    D: Driver,
+    N: Node<D>,
+    Fb: Node<D>,
+{
+    pub fn new<F>(future: F, fallback: Fb) -> Suspense<D, N, Fb>
+    where
+        F: Future<Output = N> + 'static,
+    {
+        Suspense {
+            key: None,
+            future: Some(Box::pin(future)),
+            state: Rc::new(RefCell::new(SuspenseState {
+                anchor: CommentStatic::new("suspense"),
+                content: SuspenseContent::Pending(fallback),
+            })),
+        }
+    }
+
+    /// Restarts the suspense with the future of this render if `key` differs from the key of the
+    /// previous one, e.g. the id of the loaded item: it's pending again, showing the new fallback
+    /// until the new future resolves, and the result of the previous future is dropped.
+    pub fn with_key<K>(mut self, key: K) -> Suspense<D, N, Fb>
+    where
+        K: Hash,
+    {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        self.key = Some(hasher.finish());
+        self
+    }
+
+    pub fn is_pending(&self) -> bool {
+        match self.state.borrow().content {
+            SuspenseContent::Pending(_) => true,
+            SuspenseContent::Resolved(_) => false,
+        }
+    }
+
+    /// Takes the future, to be spawned by the driver on mount.
+    ///
+    /// Returns `None` if it was already taken.
+    pub fn take_future(&mut self) -> Option<Pin<Box<dyn Future<Output = N>>>> {
+        self.future.take()
+    }
+
+    pub fn handle(&self) -> SuspenseHandle<D, N, Fb> {
+        SuspenseHandle {
+            state: Rc::downgrade(&self.state),
+        }
+    }
+
+    /// Visits the anchor and the current content, that is the fallback or the resolved node.
+    pub fn visit_content<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        let state = &mut *self.state.borrow_mut();
+        state.anchor.visit(index, visitor)?;
+        match &mut state.content {
+            SuspenseContent::Pending(fallback) => fallback.visit(index, visitor),
+            SuspenseContent::Resolved(node) => node.visit(index, visitor),
+        }
+    }
+
+    /// Takes over the mounted anchor and content of `ancestor`.
+    ///
+    /// If the keys differ, the future of this suspense replaces the one of `ancestor`: the
+    /// fallbacks are diffed, or the resolved content is swapped for the fallback, and the driver
+    /// has to spawn the future afterwards. Otherwise the future is dropped and the state of
+    /// `ancestor` is kept, resolved content as is.
+    pub fn diff_content<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        let restart = self.key != ancestor.key && self.future.is_some();
+        if !restart {
+            self.future = None;
+        }
+        {
+            let curr = &mut *self.state.borrow_mut();
+            let ancestor = &mut *ancestor.state.borrow_mut();
+            curr.anchor
+                .diff(curr_index, ancestor_index, &mut ancestor.anchor, differ)?;
+            mem::swap(&mut curr.anchor, &mut ancestor.anchor);
+            match (&mut curr.content, &mut ancestor.content) {
+                (SuspenseContent::Pending(curr), SuspenseContent::Pending(ancestor)) => {
+                    curr.diff(curr_index, ancestor_index, ancestor, differ)?;
+                    mem::swap(curr, ancestor);
+                }
+                (SuspenseContent::Pending(fallback), SuspenseContent::Resolved(node))
+                    if restart =>
+                {
+                    differ.on_node_removed(ancestor_index, node)?;
+                    differ.on_node_added(curr_index, fallback)?;
+                    mem::swap(&mut curr.content, &mut ancestor.content);
+                }
+                (_, SuspenseContent::Resolved(node)) => {
+                    let start = *curr_index;
+                    node.visit(curr_index, &mut SkipVisitor(PhantomData))?;
+                    *ancestor_index += *curr_index - start;
+                }
+                (SuspenseContent::Resolved(_), SuspenseContent::Pending(_)) => {
+                    unreachable!("only mounted suspense can be resolved")
+                }
+            }
+            if restart {
+                // the future of `ancestor` refers to its state, which is dropped along with it
+                mem::swap(curr, ancestor);
+                return Ok(());
+            }
+        }
+        // the spawned future refers to the state of the mounted suspense
+        self.state = ancestor.state.clone();
+        Ok(())
+    }
+}
+
+impl<D, N, Fb> Node<D> for Suspense<D, N, Fb>
+where
+    D: Driver,
+    N: Node<D> + 'static,
+    Fb: Node<D> + 'static,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_suspense(index, self)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_suspense(curr_index, ancestor_index, self, ancestor)
+    }
+}
+
+/// Resolves a suspense from its spawned future, without keeping it alive.
+pub struct SuspenseHandle<D, N, Fb>
+where
+    D: Driver,
+{
+    state: Weak<RefCell<SuspenseState<D, N, Fb>>>,
+}
+
+impl<D, N, Fb> SuspenseHandle<D, N, Fb>
+where
+    D: Driver,
+    N: Node<D>,
+    Fb: Node<D>,
+{
+    /// Swaps the fallback for `node`.
+    ///
+    /// `f` gets the anchor, the fallback and the node, to unmount the one and mount the other.
+    /// Returns `None` if the suspense is gone or was already resolved.
+    pub fn resolve<F, R>(&self, mut node: N, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut CommentStatic<D>, &mut Fb, &mut N) -> R,
+    {
+        let state = self.state.upgrade()?;
+        let state = &mut *state.borrow_mut();
+        let res = match &mut state.content {
+            SuspenseContent::Pending(fallback) => f(&mut state.anchor, fallback, &mut node),
+            SuspenseContent::Resolved(_) => return None,
+        };
+        state.content = SuspenseContent::Resolved(node);
+        Some(res)
+    }
+}
+
+/// Collects the futures of all pending suspense in a tree, for renderers that serialize the tree
+/// once, like server side rendering.
+///
+/// The collected futures resolve their suspense without calling into the driver. As resolved
+/// nodes can contain suspense themselves, renderers have to await the futures and collect again,
+/// until nothing is pending anymore.
+pub struct SuspenseCollector<'a, D> {
+    driver_ctx: &'a DriverCtx<D>,
+    pending: Vec<Pin<Box<dyn Future<Output = ()>>>>,
+}
+
+impl<'a, D> SuspenseCollector<'a, D>
+where
+    D: Driver,
+{
+    pub fn new(driver_ctx: &'a DriverCtx<D>) -> SuspenseCollector<'a, D> {
+        SuspenseCollector {
+            driver_ctx,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn into_pending(self) -> Vec<Pin<Box<dyn Future<Output = ()>>>> {
+        self.pending
+    }
+}
+
+impl<'a, D> NodeVisitor<D> for SuspenseCollector<'a, D>
+where
+    D: Driver + 'static,
+{
+    type Err = ();
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), ()>
+    where
+        T: Tag<D>,
+    {
+        tag.visit_children(self)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), ()>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comment<C>(&mut self, _index: usize, _comment: &mut C) -> Result<(), ()>
+    where
+        C: Comment<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), ()>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn on_portal<N>(&mut self, portal: &mut Portal<D, N>) -> Result<(), ()>
+    where
+        N: Node<D>,
+    {
+        portal.visit_children(self)
+    }
+
+    fn on_raw_html(&mut self, _index: usize, _raw_html: &mut RawHtml<D>) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn on_signal_text<S>(&mut self, _index: usize, _text: &mut SignalText<D, S>) -> Result<(), ()>
+    where
+        S: ReadSignal,
+        S::Value: fmt::Display,
+    {
+        Ok(())
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<D, N, Fb>,
+    ) -> Result<(), ()>
+    where
+        N: Node<D> + 'static,
+        Fb: Node<D> + 'static,
+    {
+        if let Some(future) = suspense.take_future() {
+            let handle = suspense.handle();
+            self.pending.push(Box::pin(future.map(move |node| {
+                handle.resolve(node, |_, _, _| ());
+            })));
+        }
+        suspense.visit_content(index, self)
+    }
+}
+
+/// Only advances the index past the nodes.
+struct SkipVisitor<E>(PhantomData<E>);
+
+impl<D, E> NodeVisitor<D> for SkipVisitor<E>
+where
+    D: Driver,
+{
+    type Err = E;
+
+    fn on_tag<T>(&mut self, _index: usize, _tag: &mut T) -> Result<(), E>
+    where
+        T: Tag<D>,
+    {
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), E>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comment<C>(&mut self, _index: usize, _comment: &mut C) -> Result<(), E>
+    where
+        C: Comment<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), E>
+    where
+        C: Comp<D>,
+    {
+        comp.visit_rendered(index, self)
+    }
+
+    fn on_portal<N>(&mut self, _portal: &mut Portal<D, N>) -> Result<(), E>
+    where
+        N: Node<D>,
+    {
+        Ok(())
+    }
+
+    fn on_raw_html(&mut self, _index: usize, _raw_html: &mut RawHtml<D>) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn on_signal_text<S>(&mut self, _index: usize, _text: &mut SignalText<D, S>) -> Result<(), E>
+    where
+        S: ReadSignal,
+        S::Value: fmt::Display,
+    {
+        Ok(())
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<D, N, Fb>,
+    ) -> Result<(), E>
+    where
+        N: Node<D> + 'static,
+        Fb: Node<D> + 'static,
+    {
+        suspense.visit_content(index, self)
+    }
+}
@@ -8,6 +8,19 @@ where
 
     fn tag(&self) -> &str;
 
+    /// The namespace the element is created in, when placed in an element of `parent` namespace.
+    fn namespace(&self, parent: Namespace) -> Namespace {
+        Namespace::of_element(self.tag(), parent)
+    }
+
+    /// Pre-rendered HTML of the whole subtree, set by `html!` for tags that are entirely static.
+    ///
+    /// Drivers may instantiate the subtree from it instead of creating every node one by one, as
+    /// long as they still fill in the driver stores of all the nodes in the subtree.
+    fn template(&self) -> Option<&'static str> {
+        None
+    }
+
     fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>;
@@ -32,6 +45,7 @@ where
     D: Driver,
 {
     tag: &'static str,
+    template: Option<&'static str>,
     children: C,
     attrs: A,
     driver_store: D::TagStore,
@@ -46,6 +60,23 @@ where
     pub fn new(tag: &'static str, attrs: A, children: C) -> TagStatic<D, C, A> {
         TagStatic {
             tag,
+            template: None,
+            children,
+            attrs,
+            driver_store: D::new_tag_store(),
+        }
+    }
+
+    /// Creates a tag along with the HTML `template` of its (fully static) subtree.
+    pub fn with_template(
+        tag: &'static str,
+        template: &'static str,
+        attrs: A,
+        children: C,
+    ) -> TagStatic<D, C, A> {
+        TagStatic {
+            tag,
+            template: Some(template),
             children,
             attrs,
             driver_store: D::new_tag_store(),
@@ -67,6 +98,10 @@ where
         self.tag
     }
 
+    fn template(&self) -> Option<&'static str> {
+        self.template
+    }
+
     fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -107,6 +142,8 @@ where
     C: Node<D>,
     A: AttrList<D>,
 {
+    const IS_STATIC: bool = C::IS_STATIC && A::IS_STATIC;
+
     fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -126,9 +163,17 @@ where
     where
         ND: NodeDiffer<D>,
     {
-        debug_assert_eq!(self.tag, ancestor.tag);
-
-        differ.on_tag(*curr_index, *ancestor_index, self, ancestor)?;
+        // the template is rendered from the literals of the whole subtree, so the same template
+        // means the same subtree, while static values could still be picked at runtime
+        let is_same_subtree = match (self.template, ancestor.template) {
+            (Some(curr), Some(ancestor)) => ptr::eq(curr, ancestor),
+            _ => false,
+        };
+        if Self::IS_STATIC && is_same_subtree {
+            mem::swap(self, ancestor);
+        } else {
+            differ.on_tag(*curr_index, *ancestor_index, self, ancestor)?;
+        }
         *curr_index += 1;
         *ancestor_index += 1;
         Ok(())
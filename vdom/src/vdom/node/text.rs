@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 pub trait Text<D>
 where
@@ -38,7 +39,7 @@ where
     }
 
     fn get(&self) -> &str {
-        &self.text
+        self.text
     }
 
     fn driver_store(&mut self) -> &mut D::TextStore {
@@ -50,6 +51,8 @@ impl<D> Node<D> for TextStatic<D>
 where
     D: Driver,
 {
+    const IS_STATIC: bool = true;
+
     fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -69,8 +72,12 @@ where
     where
         ND: NodeDiffer<D>,
     {
-        debug_assert_eq!(self.text, ancestor.text);
-        differ.on_text(*curr_index, *ancestor_index, self, ancestor)?;
+        // the text may still be picked at runtime, e.g. `if x { "a" } else { "b" }`
+        if ptr::eq(self.text, ancestor.text) {
+            mem::swap(self, ancestor);
+        } else {
+            differ.on_text(*curr_index, *ancestor_index, self, ancestor)?;
+        }
         *curr_index += 1;
         *ancestor_index += 1;
         Ok(())
@@ -146,3 +153,96 @@ where
         Ok(())
     }
 }
+
+/// A text holding a typed value, that is only formatted when it changed.
+///
+/// Unchanged values are compared in `diff` and don't call into the driver at all.
+pub struct DisplayText<D, T>
+where
+    D: Driver,
+{
+    value: T,
+    text: Option<String>,
+    driver_store: D::TextStore,
+}
+
+impl<D, T> DisplayText<D, T>
+where
+    D: Driver,
+    T: fmt::Display + PartialEq,
+{
+    pub fn new(value: T) -> DisplayText<D, T> {
+        DisplayText {
+            value,
+            text: None,
+            driver_store: D::new_text_store(),
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn format(&mut self) {
+        if self.text.is_none() {
+            self.text = Some(self.value.to_string());
+        }
+    }
+}
+
+impl<D, T> Text<D> for DisplayText<D, T>
+where
+    D: Driver,
+    T: fmt::Display + PartialEq,
+{
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    /// Empty until the node was visited or diffed.
+    fn get(&self) -> &str {
+        self.text.as_ref().map_or("", String::as_str)
+    }
+
+    fn driver_store(&mut self) -> &mut D::TextStore {
+        &mut self.driver_store
+    }
+}
+
+impl<D, T> Node<D> for DisplayText<D, T>
+where
+    D: Driver,
+    T: fmt::Display + PartialEq,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.format();
+        visitor.on_text(*index, self)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        if self.value == ancestor.value {
+            mem::swap(&mut self.text, &mut ancestor.text);
+            mem::swap(&mut self.driver_store, &mut ancestor.driver_store);
+        } else {
+            self.format();
+            differ.on_text(*curr_index, *ancestor_index, self, ancestor)?;
+        }
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::HashSet;
+
+/// Elements that are dropped along with their content, instead of being unwrapped.
+const DROPPED_TAGS: &[&str] = &[
+    "script", "style", "iframe", "object", "embed", "template", "noscript", "title", "textarea",
+];
+
+const DEFAULT_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "code",
+    "dd",
+    "del",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+const DEFAULT_ATTRS: &[&str] = &[
+    "alt", "class", "colspan", "dir", "height", "href", "lang", "rowspan", "src", "title", "width",
+];
+
+/// Attributes holding URLs, which are dropped if they would run script.
+const URL_ATTRS: &[&str] = &["href", "src", "action", "formaction", "xlink:href"];
+
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// An allow-list based cleaner for untrusted markup, to be used with `RawHtml`.
+///
+/// Tags that aren't allowed are unwrapped, keeping their text, except for scripts, styles and
+/// embedded content, which are dropped as a whole. Attributes that aren't allowed, event handler
+/// attributes (`on*`) and `javascript:` URLs are dropped. Comments and doctypes are removed.
+pub struct Sanitizer {
+    tags: HashSet<String>,
+    attrs: HashSet<String>,
+}
+
+impl Sanitizer {
+    /// A sanitizer that doesn't allow any tags or attributes, leaving only the text.
+    pub fn empty() -> Sanitizer {
+        Sanitizer {
+            tags: HashSet::new(),
+            attrs: HashSet::new(),
+        }
+    }
+
+    pub fn allow_tag(mut self, tag: &str) -> Sanitizer {
+        self.tags.insert(tag.to_ascii_lowercase());
+        self
+    }
+
+    /// Allows the attribute on all the allowed tags; event handler attributes are never allowed.
+    pub fn allow_attr(mut self, attr: &str) -> Sanitizer {
+        self.attrs.insert(attr.to_ascii_lowercase());
+        self
+    }
+
+    pub fn sanitize(&self, html: &str) -> String {
+        let mut out = String::with_capacity(html.len());
+        let mut rest = html;
+        while let Some(pos) = rest.find('<') {
+            out.push_str(&rest[..pos]);
+            rest = &rest[pos..];
+            rest = if rest.starts_with("<!--") {
+                skip_past(rest, "-->")
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                skip_past(rest, ">")
+            } else if let Some((tag, after)) = parse_tag(rest) {
+                if !DROPPED_TAGS.contains(&tag.name.as_str()) {
+                    self.write_tag(&tag, &mut out);
+                    after
+                } else if tag.is_closing {
+                    after
+                } else {
+                    skip_closing_tag(after, &tag.name)
+                }
+            } else {
+                out.push_str("&lt;");
+                &rest[1..]
+            };
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn write_tag(&self, tag: &ParsedTag<'_>, out: &mut String) {
+        if !self.tags.contains(&tag.name) {
+            return;
+        }
+        if tag.is_closing {
+            if !VOID_TAGS.contains(&tag.name.as_str()) {
+                out.push_str("</");
+                out.push_str(&tag.name);
+                out.push('>');
+            }
+            return;
+        }
+
+        out.push('<');
+        out.push_str(&tag.name);
+        for (name, value) in &tag.attrs {
+            // the checks run on the value the browser would see, which is then escaped again
+            let value = value.map(decode_refs);
+            if !self.is_attr_allowed(name, &value) {
+                continue;
+            }
+            out.push(' ');
+            out.push_str(name);
+            if let Some(value) = value {
+                out.push_str("=\"");
+                escape_attr(&value, out);
+                out.push('"');
+            }
+        }
+        out.push('>');
+    }
+
+    fn is_attr_allowed(&self, name: &str, value: &Option<String>) -> bool {
+        if name.starts_with("on") || !self.attrs.contains(name) {
+            return false;
+        }
+        match value {
+            Some(value) if URL_ATTRS.contains(&name) => is_safe_url(value),
+            _ => true,
+        }
+    }
+}
+
+/// Allows common formatting, lists, tables, links and images.
+impl Default for Sanitizer {
+    fn default() -> Sanitizer {
+        let sanitizer = DEFAULT_TAGS
+            .iter()
+            .fold(Sanitizer::empty(), |s, tag| s.allow_tag(tag));
+        DEFAULT_ATTRS
+            .iter()
+            .fold(sanitizer, |s, attr| s.allow_attr(attr))
+    }
+}
+
+struct ParsedTag<'a> {
+    name: String,
+    is_closing: bool,
+    attrs: Vec<(String, Option<&'a str>)>,
+}
+
+/// Parses the tag at the start of `s`, returning it along with the rest of the markup.
+fn parse_tag(s: &str) -> Option<(ParsedTag<'_>, &str)> {
+    let mut rest = &s[1..];
+    let is_closing = rest.starts_with('/');
+    if is_closing {
+        rest = &rest[1..];
+    }
+    if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let name_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(rest.len());
+    let name = rest[..name_len].to_ascii_lowercase();
+    rest = &rest[name_len..];
+
+    let mut attrs = Vec::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            // an unterminated tag is dropped along with the rest of the markup
+            return Some((
+                ParsedTag {
+                    name,
+                    is_closing,
+                    attrs: Vec::new(),
+                },
+                rest,
+            ));
+        }
+        if rest.starts_with('>') {
+            rest = &rest[1..];
+            break;
+        }
+
+        let attr_len = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len())
+            .max(1);
+        let attr_name = rest[..attr_len].to_ascii_lowercase();
+        rest = rest[attr_len..].trim_start();
+
+        let value = if rest.starts_with('=') {
+            rest = rest[1..].trim_start();
+            let (value, after) = match rest.chars().next() {
+                Some(quote @ '"') | Some(quote @ '\'') => {
+                    let end = rest[1..].find(quote).map_or(rest.len(), |end| end + 1);
+                    (&rest[1..end], rest.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            rest = after;
+            Some(value)
+        } else {
+            None
+        };
+        attrs.push((attr_name, value));
+    }
+
+    Some((
+        ParsedTag {
+            name,
+            is_closing,
+            attrs,
+        },
+        rest,
+    ))
+}
+
+fn skip_past<'a>(s: &'a str, end: &str) -> &'a str {
+    match s.find(end) {
+        Some(pos) => &s[pos + end.len()..],
+        None => "",
+    }
+}
+
+/// Skips everything up to and including the closing tag of `name`.
+fn skip_closing_tag<'a>(s: &'a str, name: &str) -> &'a str {
+    let closing = format!("</{}", name);
+    let lower = s.to_ascii_lowercase();
+    match lower.find(&closing) {
+        Some(pos) => skip_past(&s[pos..], ">"),
+        None => "",
+    }
+}
+
+/// Decodes the character references in an attribute value, e.g. `&#106;` or `&colon;`.
+///
+/// Numeric references don't need the trailing `;`, like in browsers. Unknown named references are
+/// kept as is, they are escaped on output and so stay literal text.
+fn decode_refs(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        let decoded = match rest.strip_prefix('#') {
+            Some(digits) => decode_numeric_ref(digits),
+            None => decode_named_ref(rest),
+        };
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => out.push('&'),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decodes the digits after `&#`, returning the character and the length of the reference
+/// after the `&`.
+fn decode_numeric_ref(s: &str) -> Option<(char, usize)> {
+    let (radix, prefix_len) = if s.starts_with('x') || s.starts_with('X') {
+        (16, 1)
+    } else {
+        (10, 0)
+    };
+    let digits = &s[prefix_len..];
+    let digits_len = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    if digits_len == 0 {
+        return None;
+    }
+    let code = u32::from_str_radix(&digits[..digits_len], radix).unwrap_or(u32::MAX);
+    let ch = match code {
+        0 => '\u{fffd}',
+        code => std::char::from_u32(code).unwrap_or('\u{fffd}'),
+    };
+    let len = 1 + prefix_len + digits_len;
+    Some(match digits[digits_len..].starts_with(';') {
+        true => (ch, len + 1),
+        false => (ch, len),
+    })
+}
+
+/// Decodes the named references that are markup, whitespace or URL punctuation, returning the
+/// character and the length of the reference after the `&`.
+fn decode_named_ref(s: &str) -> Option<(char, usize)> {
+    const REFS: &[(&str, char)] = &[
+        ("amp;", '&'),
+        ("apos;", '\''),
+        ("colon;", ':'),
+        ("commat;", '@'),
+        ("gt;", '>'),
+        ("lpar;", '('),
+        ("lt;", '<'),
+        ("nbsp;", '\u{a0}'),
+        ("NewLine;", '\n'),
+        ("num;", '#'),
+        ("percnt;", '%'),
+        ("period;", '.'),
+        ("quest;", '?'),
+        ("quot;", '"'),
+        ("rpar;", ')'),
+        ("sol;", '/'),
+        ("Tab;", '\t'),
+    ];
+    REFS.iter()
+        .find(|(name, _)| s.starts_with(name))
+        .map(|(name, ch)| (*ch, name.len()))
+}
+
+/// URLs are compared the way browsers parse them, ignoring case, whitespace and control chars.
+fn is_safe_url(url: &str) -> bool {
+    let scheme: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .take_while(|c| *c != ':')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let has_scheme = url.contains(':') && !scheme.contains(&['/', '?', '#'][..]);
+    !has_scheme || !["javascript", "vbscript", "data"].contains(&scheme.as_str())
+}
+
+fn escape_attr(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_entity_encoded_script_urls() {
+        let sanitizer = Sanitizer::default();
+        for html in &[
+            r#"<a href="&#106;avascript:alert(1)">x</a>"#,
+            r#"<a href="&#x6A;avascript:alert(1)">x</a>"#,
+            r#"<a href="&#106avascript:alert(1)">x</a>"#,
+            r#"<a href="javascript&colon;alert(1)">x</a>"#,
+            r#"<a href="java&Tab;script:alert(1)">x</a>"#,
+            r#"<a href=" &#1;javascript:alert(1)">x</a>"#,
+        ] {
+            assert_eq!(sanitizer.sanitize(html), "<a>x</a>", "{}", html);
+        }
+    }
+
+    #[test]
+    fn escapes_attribute_values() {
+        let sanitizer = Sanitizer::default();
+        assert_eq!(
+            sanitizer.sanitize(r#"<a href="/?a=1&amp;b=2&c=3" title='"&lt;'>x</a>"#),
+            r#"<a href="/?a=1&amp;b=2&amp;c=3" title="&quot;&lt;">x</a>"#
+        );
+        assert_eq!(
+            sanitizer.sanitize(r#"<span title="&unknown;">x</span>"#),
+            r#"<span title="&amp;unknown;">x</span>"#
+        );
+    }
+
+    #[test]
+    fn strips_event_handler_attributes() {
+        let sanitizer = Sanitizer::default().allow_attr("onclick");
+        assert_eq!(
+            sanitizer.sanitize(r#"<img src="a.png" onerror="alert(1)" OnLoad=alert(1) onclick>"#),
+            r#"<img src="a.png">"#
+        );
+    }
+
+    #[test]
+    fn unwraps_or_drops_disallowed_tags() {
+        let sanitizer = Sanitizer::default();
+        assert_eq!(
+            sanitizer.sanitize("<form><b>bold</b> <blink>text</blink></form>"),
+            "<b>bold</b> text"
+        );
+        assert_eq!(
+            sanitizer.sanitize("a<script>alert(1)</script>b<STYLE>p {}</style >c<!-- x -->d"),
+            "abcd"
+        );
+        assert_eq!(Sanitizer::empty().sanitize("<p>a <i>b</i></p>"), "a b");
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+/// A value that can be observed, see `Signal` and `Memo`.
+pub trait ReadSignal: Clone + 'static {
+    type Value: 'static;
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Value) -> R;
+
+    /// Calls `f` with the new value on every change, as long as the subscription is kept.
+    fn subscribe<F>(&self, f: F) -> Subscription
+    where
+        F: Fn(&Self::Value) + 'static;
+
+    /// Whether both are handles to the same value.
+    fn ptr_eq(&self, other: &Self) -> bool;
+
+    fn get(&self) -> Self::Value
+    where
+        Self::Value: Clone,
+    {
+        self.with(Clone::clone)
+    }
+
+    /// Derives a value, which is only updated (and notifies) when it actually changes.
+    fn map<F, T>(&self, f: F) -> Memo<T>
+    where
+        F: Fn(&Self::Value) -> T + 'static,
+        T: PartialEq + 'static,
+    {
+        Memo::new(self, f)
+    }
+}
+
+type Subscriber<T> = dyn Fn(&T);
+
+struct SignalInner<T> {
+    /// Shared with the running notifications, which call the subscribers without borrowing.
+    value: Rc<T>,
+    /// Bumped on every change, for notifications to tell whether they're outdated.
+    version: u64,
+    subscribers: Vec<Weak<Subscriber<T>>>,
+}
+
+/// A single threaded value, which notifies its subscribers whenever it is set.
+///
+/// Bound to texts with `SignalText` and to attributes with `SignalAttr`, a change patches the
+/// mounted nodes right away, without rendering the component again.
+pub struct Signal<T> {
+    inner: Rc<RefCell<SignalInner<T>>>,
+}
+
+impl<T> Signal<T>
+where
+    T: 'static,
+{
+    pub fn new(value: T) -> Signal<T> {
+        Signal {
+            inner: Rc::new(RefCell::new(SignalInner {
+                value: Rc::new(value),
+                version: 0,
+                subscribers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Replaces the value and notifies the subscribers.
+    ///
+    /// Subscribers may read and set the signal again, in which case the subscribers that weren't
+    /// called yet only get the latest value.
+    pub fn set(&self, value: T) {
+        self.inner.borrow_mut().value = Rc::new(value);
+        self.notify();
+    }
+
+    fn notify(&self) {
+        let (value, version, subscribers) = {
+            let inner = &mut *self.inner.borrow_mut();
+            inner.version += 1;
+            inner.subscribers.retain(|s| s.strong_count() > 0);
+            let subscribers: Vec<_> = inner.subscribers.iter().filter_map(Weak::upgrade).collect();
+            (inner.value.clone(), inner.version, subscribers)
+        };
+        for subscriber in subscribers {
+            // a change from a subscriber notified everyone of the newer value already
+            if self.inner.borrow().version != version {
+                break;
+            }
+            subscriber(&value);
+        }
+    }
+}
+
+impl<T> Signal<T>
+where
+    T: Clone + 'static,
+{
+    /// Modifies a clone of the value, then sets it and notifies the subscribers, see `Signal::set`.
+    ///
+    /// The signal isn't borrowed while `f` runs, so `f` may read it.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut value = self.inner.borrow().value.clone();
+        f(Rc::make_mut(&mut value));
+        self.inner.borrow_mut().value = value;
+        self.notify();
+    }
+}
+
+impl<T> ReadSignal for Signal<T>
+where
+    T: 'static,
+{
+    type Value = T;
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        f(&self.inner.borrow().value)
+    }
+
+    fn subscribe<F>(&self, f: F) -> Subscription
+    where
+        F: Fn(&T) + 'static,
+    {
+        let f: Rc<Subscriber<T>> = Rc::new(f);
+        self.inner.borrow_mut().subscribers.push(Rc::downgrade(&f));
+        Subscription { _f: Box::new(f) }
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Clone for Signal<T> {
+    fn clone(&self) -> Self {
+        Signal {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// A value derived from another signal, see `ReadSignal::map`.
+///
+/// Memos are meant to be created once, e.g. in `Comp::new`, as nodes bound to a new memo are
+/// bound again.
+pub struct Memo<T> {
+    signal: Signal<T>,
+    _source: Rc<Subscription>,
+}
+
+impl<T> Memo<T>
+where
+    T: PartialEq + 'static,
+{
+    pub fn new<S, F>(source: &S, f: F) -> Memo<T>
+    where
+        S: ReadSignal,
+        F: Fn(&S::Value) -> T + 'static,
+    {
+        let signal = Signal::new(source.with(&f));
+        let inner = Rc::downgrade(&signal.inner);
+        let subscription = source.subscribe(move |value| {
+            if let Some(inner) = inner.upgrade() {
+                let signal = Signal { inner };
+                let value = f(value);
+                if signal.with(|v| v != &value) {
+                    signal.set(value);
+                }
+            }
+        });
+        Memo {
+            signal,
+            _source: Rc::new(subscription),
+        }
+    }
+}
+
+impl<T> ReadSignal for Memo<T>
+where
+    T: 'static,
+{
+    type Value = T;
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.signal.with(f)
+    }
+
+    fn subscribe<F>(&self, f: F) -> Subscription
+    where
+        F: Fn(&T) + 'static,
+    {
+        self.signal.subscribe(f)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        self.signal.ptr_eq(&other.signal)
+    }
+}
+
+impl<T> Clone for Memo<T> {
+    fn clone(&self) -> Self {
+        Memo {
+            signal: self.signal.clone(),
+            _source: self._source.clone(),
+        }
+    }
+}
+
+/// Keeps a subscriber of a signal alive; dropping it unsubscribes.
+pub struct Subscription {
+    _f: Box<dyn Any>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn notifies_memos_on_changes_only() {
+        let count = Signal::new(1);
+        let is_even = count.map(|count| count % 2 == 0);
+        let notified = Rc::new(Cell::new(0));
+        let _subscription = {
+            let notified = notified.clone();
+            is_even.subscribe(move |_| notified.set(notified.get() + 1))
+        };
+
+        count.set(3);
+        assert_eq!(notified.get(), 0);
+        count.set(4);
+        count.update(|count| *count += 2);
+        assert_eq!(notified.get(), 1);
+        assert!(is_even.get());
+    }
+
+    #[test]
+    fn allows_updates_from_subscribers() {
+        let count = Signal::new(0);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let _subscriptions: Vec<_> = (0..2)
+            .map(|_| {
+                let count_handle = count.clone();
+                let seen = seen.clone();
+                count.subscribe(move |&value| {
+                    seen.borrow_mut().push(value);
+                    // clamps the count, reading and setting it from within the notification
+                    if value > 10 {
+                        count_handle.set(count_handle.get() - 10);
+                    }
+                })
+            })
+            .collect();
+
+        count.set(12);
+        assert_eq!(*seen.borrow(), [12, 2, 2]);
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn allows_reads_from_updates() {
+        let count = Signal::new(1);
+        let double = count.map(|count| count * 2);
+        count.update(|count_mut| *count_mut += count.get() + double.get());
+        assert_eq!(count.get(), 4);
+        assert_eq!(double.get(), 8);
+    }
+}
@@ -1,57 +1,133 @@
-use crate::parser::{Attr, AttrValue, Node, Tag};
+use crate::{
+    parser::{Attr, AttrValue, Node, Tag, TagName, Text},
+    template,
+};
 use quote::{__rt::TokenStream, quote, ToTokens};
 use syn::LitStr;
 
 pub fn gen_nodes(nodes: Vec<Node>) -> TokenStream {
-    nodes
-        .into_iter()
-        .map(gen_node)
-        .fold(None, |prev_nodes, node| {
-            match prev_nodes {
-                Some(prev_nodes) => Some(quote! {(#prev_nodes, #node)}),
-                None => Some(node),
-            }
-        })
-        .unwrap_or_else(|| quote! {()})
+    gen_child_nodes(nodes, false)
+}
+
+/// `in_template` is set for nodes below a tag that already carries the template of its subtree.
+fn gen_child_nodes(nodes: Vec<Node>, in_template: bool) -> TokenStream {
+    gen_tuple(
+        nodes
+            .into_iter()
+            .map(|node| gen_node(node, in_template))
+            .collect(),
+    )
 }
 
-fn gen_node(node: Node) -> TokenStream {
+/// The largest tuple `Node` and `AttrList` are implemented for.
+const MAX_TUPLE_LEN: usize = 16;
+
+/// Groups the items into a flat tuple, nesting tuples only beyond `MAX_TUPLE_LEN` items.
+fn gen_tuple(mut items: Vec<TokenStream>) -> TokenStream {
+    match items.len() {
+        0 => quote! {()},
+        1 => items.pop().unwrap(),
+        len if len <= MAX_TUPLE_LEN => quote! {(#(#items),*)},
+        _ => {
+            let chunks = items
+                .chunks(MAX_TUPLE_LEN)
+                .map(|chunk| gen_tuple(chunk.to_vec()))
+                .collect();
+            gen_tuple(chunks)
+        }
+    }
+}
+
+fn gen_node(node: Node, in_template: bool) -> TokenStream {
     match node {
-        Node::Tag(tag) => gen_tag(tag),
-        Node::Text(lit_str) => quote! {vdom::vdom::node::TextStatic::new(#lit_str)},
+        Node::Tag(tag) => gen_tag(tag, in_template),
+        Node::Text(text) => gen_text(text),
         Node::Expr(expr) => expr.into_token_stream(),
     }
 }
 
-fn gen_tag(tag: Tag) -> TokenStream {
-    let tag_tag = LitStr::new(&tag.tag.to_string(), tag.tag.span());
+fn gen_text(text: Text) -> TokenStream {
+    match text {
+        Text::Static(lit_str) => quote! {vdom::vdom::node::TextStatic::new(#lit_str)},
+        Text::Format(fmt, args) => {
+            quote! {
+                vdom::vdom::node::TextDyn::new(format!(#fmt, #(#args),*))
+            }
+        }
+    }
+}
 
-    let attrs = tag
+fn gen_tag(tag: Tag, in_template: bool) -> TokenStream {
+    let template = if in_template {
+        None
+    } else {
+        template::render(&tag)
+    };
+
+    let (style_props, attrs): (Vec<_>, Vec<_>) = tag
         .attrs
         .into_iter()
-        .map(gen_attr)
-        .map(|attr| quote! {vdom::vdom::attr::AttrListEntry(#attr)})
-        .fold(None, |prev_attrs, attr| {
-            match prev_attrs {
-                Some(prev_attrs) => Some(quote! {(#prev_attrs, #attr)}),
-                None => Some(attr),
-            }
-        })
-        .unwrap_or_else(|| quote! {()});
+        .partition(|attr| attr.name.strip_prefix("style").is_some());
+    let (classes, attrs): (Vec<_>, Vec<_>) = attrs
+        .into_iter()
+        .partition(|attr| attr.name.strip_prefix("class").is_some());
+    let (node_refs, attrs): (Vec<_>, Vec<_>) =
+        attrs.into_iter().partition(|attr| attr.name.value == "ref");
+    let style = gen_style(style_props);
+    let class_list = gen_class_list(classes);
+    let spreads = tag.spreads.into_iter().map(ToTokens::into_token_stream);
+    let attrs = gen_tuple(
+        attrs
+            .into_iter()
+            .map(gen_attr)
+            .map(|attr| quote! {vdom::vdom::attr::AttrListEntry(#attr)})
+            .chain(style)
+            .chain(class_list)
+            .chain(node_refs.into_iter().map(gen_node_ref))
+            .chain(spreads)
+            .collect(),
+    );
 
-    let children = gen_nodes(tag.children);
+    let children = gen_child_nodes(tag.children, in_template || template.is_some());
 
-    quote! {
-        vdom::vdom::node::TagStatic::new(
-            #tag_tag,
-            #attrs,
-            #children,
-        )
+    let tag_tag = match tag.tag {
+        TagName::Static(name) => LitStr::new(&name.value, name.span),
+        TagName::Dyn(expr) => {
+            return quote! {
+                vdom::vdom::node::TagDyn::new(
+                    #expr,
+                    #attrs,
+                    #children,
+                )
+            };
+        }
+    };
+
+    match template {
+        Some(template) => {
+            quote! {
+                vdom::vdom::node::TagStatic::with_template(
+                    #tag_tag,
+                    #template,
+                    #attrs,
+                    #children,
+                )
+            }
+        }
+        None => {
+            quote! {
+                vdom::vdom::node::TagStatic::new(
+                    #tag_tag,
+                    #attrs,
+                    #children,
+                )
+            }
+        }
     }
 }
 
 fn gen_attr(attr: Attr) -> TokenStream {
-    let name = LitStr::new(&attr.name.to_string(), attr.name.span());
+    let name = LitStr::new(&attr.name.value, attr.name.span);
 
     match attr.value {
         AttrValue::Str(lit_str) => {
@@ -74,3 +150,53 @@ fn gen_attr(attr: Attr) -> TokenStream {
         }
     }
 }
+
+/// Clones the handle of `ref=(node_ref)`, so it can be borrowed from the component.
+fn gen_node_ref(attr: Attr) -> TokenStream {
+    match attr.value {
+        AttrValue::Expr(expr) => quote! {vdom::vdom::attr::NodeRef::clone(&#expr)},
+        AttrValue::Str(_) | AttrValue::True => unreachable!(),
+    }
+}
+
+/// Collects the `style:prop=value` attributes into a single `Style`.
+fn gen_style(props: Vec<Attr>) -> Option<TokenStream> {
+    if props.is_empty() {
+        return None;
+    }
+
+    let props = props.into_iter().map(|attr| {
+        let prop = LitStr::new(attr.name.strip_prefix("style").unwrap(), attr.name.span);
+        let value = match attr.value {
+            AttrValue::Str(lit_str) => lit_str.into_token_stream(),
+            AttrValue::Expr(expr) => expr.into_token_stream(),
+            AttrValue::True => unreachable!(),
+        };
+        quote! {.with(#prop, #value)}
+    });
+
+    Some(quote! {
+        vdom::vdom::attr::Style::new()#(#props)*
+    })
+}
+
+/// Collects the `class:name=(active)` attributes into a single `ClassList`.
+fn gen_class_list(classes: Vec<Attr>) -> Option<TokenStream> {
+    if classes.is_empty() {
+        return None;
+    }
+
+    let classes = classes.into_iter().map(|attr| {
+        let class = LitStr::new(attr.name.strip_prefix("class").unwrap(), attr.name.span);
+        let active = match attr.value {
+            AttrValue::Expr(expr) => expr.into_token_stream(),
+            AttrValue::True => quote! {true},
+            AttrValue::Str(_) => unreachable!(),
+        };
+        quote! {.with(#class, #active)}
+    });
+
+    Some(quote! {
+        vdom::vdom::attr::ClassList::new()#(#classes)*
+    })
+}
@@ -4,13 +4,23 @@ extern crate proc_macro;
 
 mod code_gen;
 mod parser;
+mod template;
+mod validate;
 
 use crate::parser::Nodes;
 use crate::proc_macro::TokenStream;
 use syn::parse_macro_input;
 
+/// Builds a node tree from its HTML-like description.
+///
+/// Text literals are `format!` strings: `"{count} items"` renders `count` into the text. Literal
+/// braces have to be doubled (`{{`, `}}`), so text written before placeholders were supported
+/// that contains braces needs to be escaped.
 #[proc_macro]
 pub fn html(input: TokenStream) -> TokenStream {
     let nodes = parse_macro_input!(input as Nodes);
+    if let Err(errors) = validate::validate_nodes(&nodes.nodes) {
+        return errors.into();
+    }
     code_gen::gen_nodes(nodes.nodes).into()
 }
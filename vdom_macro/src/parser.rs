@@ -1,9 +1,10 @@
+use quote::__rt::Span;
 use syn::{
     braced, bracketed,
     ext::IdentExt,
     parenthesized,
     parse::{Parse, ParseStream, Result},
-    token, Expr, Ident, LitStr, Token,
+    parse_quote, token, Error, Expr, Ident, LitStr, Token,
 };
 
 #[derive(Debug)]
@@ -24,13 +25,15 @@ impl Parse for Nodes {
 #[derive(Debug)]
 pub enum Node {
     Tag(Tag),
-    Text(LitStr),
+    Text(Text),
     Expr(Expr),
 }
 
 impl Parse for Node {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let res = if input.peek(token::Paren) {
+        let res = if input.peek(token::Paren) && starts_dyn_tag(input)? {
+            Node::Tag(input.parse()?)
+        } else if input.peek(token::Paren) {
             let expr;
             parenthesized!(expr in input);
             Node::Expr(expr.parse()?)
@@ -43,20 +46,249 @@ impl Parse for Node {
     }
 }
 
+/// `(expr)` names a tag at runtime if it's followed by attributes, children in braces or `;`,
+/// otherwise it's an expression node.
+fn starts_dyn_tag(input: ParseStream<'_>) -> Result<bool> {
+    let fork = input.fork();
+    let _expr;
+    parenthesized!(_expr in fork);
+    Ok(fork.peek(token::Brace)
+        || fork.peek(Token![;])
+        || fork.peek(Token![..])
+        || fork.fork().parse::<Attr>().is_ok())
+}
+
+/// A text literal, either plain or containing `format!`-style `{expr}` placeholders.
+#[derive(Debug)]
+pub enum Text {
+    Static(LitStr),
+    Format(LitStr, Vec<Expr>),
+}
+
+impl Parse for Text {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let lit_str: LitStr = input.parse()?;
+        let value = lit_str.value();
+
+        let mut text = String::new();
+        let mut fmt = String::new();
+        let mut args = Vec::new();
+        let mut chars = value.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                    fmt.push_str("{{");
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                    fmt.push_str("}}");
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(Error::new(lit_str.span(), "unclosed `{` in text"));
+                            }
+                        }
+                    }
+                    let (expr, spec) = split_placeholder(&placeholder);
+                    if expr.trim().is_empty() {
+                        return Err(Error::new(
+                            lit_str.span(),
+                            "text placeholders must name an expression, e.g. `{count}`",
+                        ));
+                    }
+                    let expr = syn::parse_str(expr).map_err(|err| {
+                        Error::new(
+                            lit_str.span(),
+                            format!("invalid expression `{}` in text: {}", expr, err),
+                        )
+                    })?;
+                    args.push(expr);
+                    fmt.push('{');
+                    fmt.push_str(spec);
+                    fmt.push('}');
+                }
+                '}' => {
+                    return Err(Error::new(
+                        lit_str.span(),
+                        "unmatched `}` in text, use `}}` to escape it",
+                    ));
+                }
+                c => {
+                    text.push(c);
+                    fmt.push(c);
+                }
+            }
+        }
+
+        let res = if args.is_empty() {
+            Text::Static(LitStr::new(&text, lit_str.span()))
+        } else {
+            Text::Format(LitStr::new(&fmt, lit_str.span()), args)
+        };
+        Ok(res)
+    }
+}
+
+/// Splits `expr:spec` at the first `:` that isn't part of a `::` path separator.
+fn split_placeholder(placeholder: &str) -> (&str, &str) {
+    let bytes = placeholder.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b':' {
+            if bytes.get(i + 1) == Some(&b':') {
+                i += 2;
+                continue;
+            }
+            return (&placeholder[..i], &placeholder[i..]);
+        }
+        i += 1;
+    }
+    (placeholder, "")
+}
+
+/// A tag or attribute name, made of identifiers joined by `-` (e.g. `my-elem`, `data-id`),
+/// optionally behind a `prefix:` (e.g. `style:background-color`).
+#[derive(Debug)]
+pub struct Name {
+    pub value: String,
+    pub span: Span,
+}
+
+impl Name {
+    /// Returns the rest of the name, if it starts with `prefix:`.
+    pub fn strip_prefix(&self, prefix: &str) -> Option<&str> {
+        if self.value.starts_with(prefix) && self.value[prefix.len()..].starts_with(':') {
+            Some(&self.value[prefix.len() + 1..])
+        } else {
+            None
+        }
+    }
+}
+
+impl Parse for Name {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let first = Ident::parse_any(input)?;
+        let span = first.span();
+        let mut value = first.to_string();
+        parse_dashed(input, &mut value)?;
+
+        if input.peek(Token![:]) && !input.peek(Token![::]) {
+            input.parse::<Token![:]>()?;
+            value.push(':');
+            value.push_str(&Ident::parse_any(input)?.to_string());
+            parse_dashed(input, &mut value)?;
+        }
+
+        Ok(Name { value, span })
+    }
+}
+
+/// Appends the `-ident` segments following an identifier.
+fn parse_dashed(input: ParseStream<'_>, value: &mut String) -> Result<()> {
+    while input.peek(Token![-]) {
+        let fork = input.fork();
+        fork.parse::<Token![-]>()?;
+        if Ident::parse_any(&fork).is_err() {
+            break;
+        }
+        input.parse::<Token![-]>()?;
+        value.push('-');
+        value.push_str(&Ident::parse_any(input)?.to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum TagName {
+    Static(Name),
+    Dyn(Expr),
+}
+
 #[derive(Debug)]
 pub struct Tag {
-    pub tag: Ident,
+    pub tag: TagName,
     pub attrs: Vec<Attr>,
+    /// `AttrList`s spread into the tag with `..attrs` or `..(expr)`.
+    pub spreads: Vec<Expr>,
     pub children: Vec<Node>,
 }
 
 impl Parse for Tag {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let tag = Ident::parse_any(input)?;
+        let mut classes = Vec::<Name>::new();
+        let tag = if input.peek(token::Paren) {
+            let expr;
+            parenthesized!(expr in input);
+            TagName::Dyn(expr.parse()?)
+        } else {
+            let name = input.parse()?;
+            // `..` starts a spread, not a class
+            while input.peek(Token![.]) && !input.peek(Token![..]) {
+                input.parse::<Token![.]>()?;
+                classes.push(input.parse()?);
+            }
+            TagName::Static(name)
+        };
 
-        let mut attrs = Vec::new();
-        while input.fork().parse::<Attr>().is_ok() {
-            attrs.push(input.parse()?);
+        let mut attrs = Vec::<Attr>::new();
+        let mut spreads = Vec::new();
+        loop {
+            if input.peek(Token![..]) {
+                input.parse::<Token![..]>()?;
+                spreads.push(if input.peek(token::Paren) {
+                    let expr;
+                    parenthesized!(expr in input);
+                    expr.parse()?
+                } else {
+                    let ident: Ident = input.parse()?;
+                    parse_quote!(#ident)
+                });
+            } else if input.fork().parse::<Attr>().is_ok() {
+                attrs.push(input.parse()?);
+            } else {
+                break;
+            }
+        }
+
+        // `.class` shorthands join the `class:` toggles, if there are any, otherwise they make up
+        // a plain `class` attribute
+        if attrs
+            .iter()
+            .any(|attr| attr.name.strip_prefix("class").is_some())
+        {
+            let shorthands = classes.into_iter().map(|class| {
+                Attr {
+                    name: Name {
+                        value: format!("class:{}", class.value),
+                        span: class.span,
+                    },
+                    value: AttrValue::True,
+                    condition: None,
+                }
+            });
+            attrs.splice(0..0, shorthands);
+        } else if let Some(first) = classes.first() {
+            let value = classes
+                .iter()
+                .map(|class| class.value.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            attrs.push(Attr {
+                name: Name {
+                    value: "class".to_string(),
+                    span: first.span,
+                },
+                value: AttrValue::Str(LitStr::new(&value, first.span)),
+                condition: None,
+            });
         }
 
         let mut children = Vec::new();
@@ -75,6 +307,7 @@ impl Parse for Tag {
         Ok(Tag {
             tag,
             attrs,
+            spreads,
             children,
         })
     }
@@ -82,14 +315,14 @@ impl Parse for Tag {
 
 #[derive(Debug)]
 pub struct Attr {
-    pub name: Ident,
+    pub name: Name,
     pub value: AttrValue,
     pub condition: Option<Expr>,
 }
 
 impl Parse for Attr {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let name = Ident::parse_any(input)?;
+        let name = input.parse()?;
 
         let value = if input.peek(Token![=]) {
             input.parse::<Token![=]>()?;
@@ -129,3 +362,86 @@ pub enum AttrValue {
     Expr(Expr),
     True,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_spreads_after_tag_names_and_classes() {
+        let tag = syn::parse_str::<Tag>(r#"p ..(attrs) { "text" }"#).unwrap();
+        assert_eq!(tag.spreads.len(), 1);
+        assert_eq!(tag.children.len(), 1);
+
+        let tag = syn::parse_str::<Tag>(r#"p.note ..(attrs) { "text" }"#).unwrap();
+        assert_eq!(tag.attrs.len(), 1);
+        assert_eq!(tag.attrs[0].name.value, "class");
+        assert_eq!(tag.spreads.len(), 1);
+    }
+
+    fn parse_text(text: &str) -> Result<Text> {
+        syn::parse_str::<Text>(&format!("{:?}", text))
+    }
+
+    fn format_parts(text: Text) -> (String, Vec<String>) {
+        match text {
+            Text::Format(fmt, args) => {
+                (
+                    fmt.value(),
+                    args.iter()
+                        .map(|arg| quote::quote!(#arg).to_string())
+                        .collect(),
+                )
+            }
+            Text::Static(text) => panic!("expected placeholders in `{}`", text.value()),
+        }
+    }
+
+    #[test]
+    fn unescapes_braces_in_static_text() {
+        match parse_text("{{a}} }}").unwrap() {
+            Text::Static(text) => assert_eq!(text.value(), "{a} }"),
+            text => panic!("expected static text, got {:?}", text),
+        }
+    }
+
+    #[test]
+    fn keeps_escaped_braces_next_to_placeholders() {
+        let (fmt, args) = format_parts(parse_text("{{{count}}}").unwrap());
+        assert_eq!(fmt, "{{{}}}");
+        assert_eq!(args, ["count"]);
+    }
+
+    #[test]
+    fn passes_format_specs_through() {
+        let (fmt, args) = format_parts(parse_text("[{name:>8}] {value:.2}").unwrap());
+        assert_eq!(fmt, "[{:>8}] {:.2}");
+        assert_eq!(args, ["name", "value"]);
+    }
+
+    #[test]
+    fn does_not_split_placeholders_at_paths() {
+        let (fmt, args) = format_parts(parse_text("{a::b} {a::b:?}").unwrap());
+        assert_eq!(fmt, "{} {:?}");
+        assert_eq!(args, ["a :: b", "a :: b"]);
+    }
+
+    #[test]
+    fn rejects_malformed_placeholders() {
+        let err = parse_text("a {b").unwrap_err();
+        assert_eq!(err.to_string(), "unclosed `{` in text");
+
+        let err = parse_text("a {} b").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("text placeholders must name an expression"));
+
+        let err = parse_text("a {:>8}").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("text placeholders must name an expression"));
+
+        let err = parse_text("a } b").unwrap_err();
+        assert!(err.to_string().starts_with("unmatched `}` in text"));
+    }
+}
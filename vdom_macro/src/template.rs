@@ -0,0 +1,255 @@
+use crate::{
+    parser::{Attr, AttrValue, Node, Tag, TagName, Text},
+    validate,
+};
+
+/// Elements whose content isn't parsed as regular HTML, so their text can't be pre-rendered.
+const RAW_TEXT_TAGS: &[&str] = &[
+    "script", "style", "textarea", "title", "noscript", "template",
+];
+
+/// Elements which close an open `p`, wherever they are nested in it.
+const P_CLOSING_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+const HEADING_TAGS: &[&str] = &["h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// Renders the HTML template of a tag, if its whole subtree is static and can be instantiated by
+/// the driver without ambiguity.
+///
+/// Leaf tags aren't templated, as there would be nothing to clone but the element itself.
+pub fn render(tag: &Tag) -> Option<String> {
+    let is_html_element = match &tag.tag {
+        TagName::Static(name) => validate::is_html_element(&name.value),
+        TagName::Dyn(_) => false,
+    };
+    if tag.children.is_empty() || !is_html_element || !is_static_tag(tag) {
+        return None;
+    }
+
+    let mut html = String::new();
+    write_tag(tag, &mut html);
+    Some(html)
+}
+
+fn is_static_tag(tag: &Tag) -> bool {
+    let name = match &tag.tag {
+        TagName::Static(name) => name,
+        TagName::Dyn(_) => return false,
+    };
+    !RAW_TEXT_TAGS.contains(&name.value.as_str())
+        && !is_rewritten_by_parser(&name.value, &tag.children)
+        && tag.spreads.is_empty()
+        && tag.attrs.iter().all(is_static_attr)
+        && tag.children.iter().all(is_static_node)
+        && has_separable_texts(&tag.children)
+}
+
+/// Whether the HTML parser would rearrange the children, e.g. insert a `tbody` between `table`
+/// and `tr` or close a `p` before a `div`, so the parsed template wouldn't match the tree.
+fn is_rewritten_by_parser(name: &str, children: &[Node]) -> bool {
+    let table_content: &[&str] = match name {
+        "table" => &["caption", "colgroup", "thead", "tbody", "tfoot"],
+        "thead" | "tbody" | "tfoot" => &["tr"],
+        "tr" => &["td", "th"],
+        "colgroup" => &["col"],
+        "p" => return contains_tag(children, P_CLOSING_TAGS, &[]),
+        // nested ones close the outer one, or are dropped in the case of `form`
+        "a" | "form" | "button" => return contains_tag(children, &[name], &[]),
+        // an item closes the open one, unless it's in a nested list
+        "li" => return contains_tag(children, &["li"], &["ul", "ol", "menu"]),
+        "dd" | "dt" => return contains_tag(children, &["dd", "dt"], &["dl"]),
+        "option" => return contains_tag(children, &["option", "optgroup"], &[]),
+        "optgroup" => return contains_tag(children, &["optgroup"], &[]),
+        // a heading only closes the heading it's directly placed in
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            return children.iter().any(|child| is_tag(child, HEADING_TAGS))
+        }
+        _ => return false,
+    };
+    !children.iter().all(|child| is_tag(child, table_content))
+}
+
+fn is_tag(node: &Node, names: &[&str]) -> bool {
+    match node {
+        Node::Tag(Tag {
+            tag: TagName::Static(name),
+            ..
+        }) => names.contains(&name.value.as_str()),
+        _ => false,
+    }
+}
+
+/// Whether any of the nodes or their descendants is one of the `names`, without looking into the
+/// `boundaries`.
+fn contains_tag(nodes: &[Node], names: &[&str], boundaries: &[&str]) -> bool {
+    nodes.iter().any(|node| {
+        match node {
+            Node::Tag(tag) => {
+                is_tag(node, names)
+                    || (!is_tag(node, boundaries) && contains_tag(&tag.children, names, boundaries))
+            }
+            _ => false,
+        }
+    })
+}
+
+fn is_static_attr(attr: &Attr) -> bool {
+    if attr.name.strip_prefix("style").is_some() || attr.name.strip_prefix("class").is_some() {
+        return false;
+    }
+    match attr.value {
+        AttrValue::Str(_) | AttrValue::True => attr.condition.is_none(),
+        AttrValue::Expr(_) => false,
+    }
+}
+
+fn is_static_node(node: &Node) -> bool {
+    match node {
+        Node::Tag(tag) => is_static_tag(tag),
+        Node::Text(Text::Static(_)) => true,
+        Node::Text(Text::Format(..)) | Node::Expr(_) => false,
+    }
+}
+
+/// The HTML parser merges adjacent texts and drops empty ones, which would break the mapping of
+/// the text nodes onto the parsed template.
+fn has_separable_texts(children: &[Node]) -> bool {
+    let mut prev_is_text = false;
+    for child in children {
+        match child {
+            Node::Text(Text::Static(lit_str)) => {
+                if prev_is_text || lit_str.value().is_empty() {
+                    return false;
+                }
+                prev_is_text = true;
+            }
+            _ => prev_is_text = false,
+        }
+    }
+    true
+}
+
+fn write_tag(tag: &Tag, html: &mut String) {
+    let name = match &tag.tag {
+        TagName::Static(name) => name.value.as_str(),
+        TagName::Dyn(_) => unreachable!(),
+    };
+
+    html.push('<');
+    html.push_str(name);
+    for attr in &tag.attrs {
+        html.push(' ');
+        html.push_str(&attr.name.value);
+        html.push_str("=\"");
+        match &attr.value {
+            AttrValue::Str(lit_str) => escape(&lit_str.value(), true, html),
+            // mirrors the drivers, which set `true` attributes to their own name
+            AttrValue::True => escape(&attr.name.value, true, html),
+            AttrValue::Expr(_) => unreachable!(),
+        }
+        html.push('"');
+    }
+    html.push('>');
+
+    if validate::is_void_element(name) {
+        return;
+    }
+
+    for child in &tag.children {
+        match child {
+            Node::Tag(tag) => write_tag(tag, html),
+            Node::Text(Text::Static(lit_str)) => escape(&lit_str.value(), false, html),
+            Node::Text(Text::Format(..)) | Node::Expr(_) => unreachable!(),
+        }
+    }
+
+    html.push_str("</");
+    html.push_str(name);
+    html.push('>');
+}
+
+fn escape(s: &str, is_attr: bool, html: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' if is_attr => html.push_str("&quot;"),
+            '\u{a0}' => html.push_str("&nbsp;"),
+            c => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_str(input: &str) -> Option<String> {
+        render(&syn::parse_str::<Tag>(input).unwrap())
+    }
+
+    #[test]
+    fn renders_static_subtrees() {
+        assert_eq!(
+            render_str(r#"ul id="list" { li "a" li { b "b" } }"#).unwrap(),
+            r#"<ul id="list"><li>a</li><li><b>b</b></li></ul>"#
+        );
+        assert_eq!(render_str(r#"p { "a" br; "b" }"#).unwrap(), "<p>a<br>b</p>");
+    }
+
+    #[test]
+    fn skips_subtrees_the_parser_rewrites() {
+        assert_eq!(render_str(r#"table { tr { td "a" } }"#), None);
+        assert_eq!(render_str(r#"tr { td "a" "b" }"#), None);
+        assert_eq!(render_str(r#"p { div "a" }"#), None);
+        assert_eq!(render_str(r#"p { span { ul { li "a" } } }"#), None);
+        assert!(render_str(r#"table { tbody { tr { td "a" th "b" } } }"#).is_some());
+        assert!(render_str(r#"div { p "a" div "b" }"#).is_some());
+
+        assert_eq!(render_str(r#"a { span { a "b" } }"#), None);
+        assert_eq!(render_str(r#"form { div { form { input; } } }"#), None);
+        assert_eq!(render_str(r#"button { b { button "a" } }"#), None);
+        assert_eq!(render_str(r#"li { "a" li "b" }"#), None);
+        assert_eq!(render_str(r#"dt { "a" dd "b" }"#), None);
+        assert_eq!(render_str(r#"option { "a" option "b" }"#), None);
+        assert_eq!(render_str(r#"optgroup { "a" optgroup "b" }"#), None);
+        assert_eq!(render_str(r#"h1 { "a" h2 "b" }"#), None);
+        assert!(render_str(r#"li { "a" ul { li "b" } }"#).is_some());
+        assert!(render_str(r#"dd { "a" dl { dt "b" } }"#).is_some());
+        assert!(render_str(r#"optgroup { option "a" option "b" }"#).is_some());
+        assert!(render_str(r#"h1 { "a" span { "b" } }"#).is_some());
+    }
+}
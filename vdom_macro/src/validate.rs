@@ -0,0 +1,774 @@
+use crate::parser::{Attr, AttrValue, Name, Node, Tag, TagName};
+use quote::{__rt::TokenStream, quote};
+use std::collections::HashSet;
+use syn::Error;
+
+/// Checks the parsed nodes against the HTML/SVG vocabulary.
+///
+/// Returns the `compile_error!`s for every problem found, so that all of them are reported at
+/// once instead of one per compilation.
+pub fn validate_nodes(nodes: &[Node]) -> Result<(), TokenStream> {
+    let mut errors = Vec::new();
+    for node in nodes {
+        validate_node(node, &mut errors);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        // wrapped in a block, as only one `compile_error!` is expanded in expression position
+        let errors = errors.iter().map(Error::to_compile_error);
+        Err(quote! {{ #(#errors;)* }})
+    }
+}
+
+fn validate_node(node: &Node, errors: &mut Vec<Error>) {
+    match node {
+        Node::Tag(tag) => validate_tag(tag, errors),
+        Node::Text(_) | Node::Expr(_) => {}
+    }
+}
+
+fn validate_tag(tag: &Tag, errors: &mut Vec<Error>) {
+    // custom elements accept any attribute, all other tags, including the ones named at runtime,
+    // only the known HTML, SVG and XML attributes
+    let is_custom = match &tag.tag {
+        TagName::Static(name) => {
+            validate_tag_name(name, !tag.children.is_empty(), errors);
+            // MathML attributes aren't listed, so they are accepted like the ones of custom tags
+            is_custom_element(&name.value) || MATHML_TAGS.contains(&name.value.as_str())
+        }
+        TagName::Dyn(_) => false,
+    };
+
+    let mut seen = HashSet::new();
+    for attr in &tag.attrs {
+        validate_attr(attr, is_custom, errors);
+        if !seen.insert(attr.name.value.as_str()) {
+            errors.push(Error::new(
+                attr.name.span,
+                format!("duplicate attribute `{}`", attr.name.value),
+            ));
+        }
+    }
+
+    for prefix in &["style", "class"] {
+        let has_prefixed = tag
+            .attrs
+            .iter()
+            .any(|attr| attr.name.strip_prefix(prefix).is_some());
+        if let Some(attr) = tag.attrs.iter().find(|attr| attr.name.value == *prefix) {
+            if has_prefixed {
+                errors.push(Error::new(
+                    attr.name.span,
+                    format!("`{0}` can't be combined with `{0}:` attributes", prefix),
+                ));
+            }
+        }
+    }
+
+    for child in &tag.children {
+        validate_node(child, errors);
+    }
+}
+
+fn validate_tag_name(name: &Name, has_children: bool, errors: &mut Vec<Error>) {
+    let value = name.value.as_str();
+
+    if !is_custom_element(value)
+        && !HTML_TAGS.contains(&value)
+        && !SVG_TAGS.contains(&value)
+        && !MATHML_TAGS.contains(&value)
+    {
+        errors.push(Error::new(
+            name.span,
+            format!(
+                "unknown element `{}`; custom elements must contain a `-`",
+                value
+            ),
+        ));
+    }
+
+    if has_children && is_void_element(value) {
+        errors.push(Error::new(
+            name.span,
+            format!("void element `{}` can't have children", value),
+        ));
+    }
+}
+
+fn validate_attr(attr: &Attr, is_custom: bool, errors: &mut Vec<Error>) {
+    let name = attr.name.value.as_str();
+
+    if attr.name.strip_prefix("style").is_some() {
+        if let AttrValue::True = attr.value {
+            errors.push(Error::new(
+                attr.name.span,
+                format!("style property `{}` needs a value", name),
+            ));
+        }
+        return;
+    }
+
+    if attr.name.strip_prefix("class").is_some() {
+        if let AttrValue::Str(_) = attr.value {
+            errors.push(Error::new(
+                attr.name.span,
+                format!("class `{}` needs a `bool` expression or `?`", name),
+            ));
+        }
+        return;
+    }
+
+    if name == "ref" {
+        if let AttrValue::Str(_) | AttrValue::True = attr.value {
+            errors.push(Error::new(
+                attr.name.span,
+                "`ref` needs a `NodeRef` expression",
+            ));
+        }
+        return;
+    }
+
+    if is_custom
+        || name.starts_with("data-")
+        || name.starts_with("aria-")
+        || HTML_ATTRS.contains(&name)
+        || SVG_ATTRS.contains(&name)
+        || XML_ATTRS.contains(&name)
+    {
+        return;
+    }
+
+    errors.push(Error::new(
+        attr.name.span,
+        format!("unknown attribute `{}`", name),
+    ));
+}
+
+/// Custom elements are required to contain a `-`, which no standard element does.
+fn is_custom_element(name: &str) -> bool {
+    name.contains('-')
+}
+
+pub fn is_void_element(name: &str) -> bool {
+    VOID_TAGS.contains(&name)
+}
+
+/// Whether the element is always created in the HTML namespace, no matter where it is placed.
+pub fn is_html_element(name: &str) -> bool {
+    is_custom_element(name) || (HTML_TAGS.contains(&name) && !SVG_TAGS.contains(&name))
+}
+
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+const HTML_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "menu",
+    "meta",
+    "meter",
+    "nav",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "param",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "search",
+    "section",
+    "select",
+    "slot",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+const SVG_TAGS: &[&str] = &[
+    "svg",
+    "a",
+    "animate",
+    "animateMotion",
+    "animateTransform",
+    "circle",
+    "clipPath",
+    "defs",
+    "desc",
+    "ellipse",
+    "feBlend",
+    "feColorMatrix",
+    "feComponentTransfer",
+    "feComposite",
+    "feConvolveMatrix",
+    "feDiffuseLighting",
+    "feDisplacementMap",
+    "feDistantLight",
+    "feDropShadow",
+    "feFlood",
+    "feFuncA",
+    "feFuncB",
+    "feFuncG",
+    "feFuncR",
+    "feGaussianBlur",
+    "feImage",
+    "feMerge",
+    "feMergeNode",
+    "feMorphology",
+    "feOffset",
+    "fePointLight",
+    "feSpecularLighting",
+    "feSpotLight",
+    "feTile",
+    "feTurbulence",
+    "filter",
+    "foreignObject",
+    "g",
+    "image",
+    "line",
+    "linearGradient",
+    "marker",
+    "mask",
+    "metadata",
+    "mpath",
+    "path",
+    "pattern",
+    "polygon",
+    "polyline",
+    "radialGradient",
+    "rect",
+    "script",
+    "set",
+    "stop",
+    "style",
+    "switch",
+    "symbol",
+    "text",
+    "textPath",
+    "title",
+    "tspan",
+    "use",
+    "view",
+];
+
+const HTML_ATTRS: &[&str] = &[
+    // global attributes
+    "accesskey",
+    "autocapitalize",
+    "autofocus",
+    "class",
+    "contenteditable",
+    "dir",
+    "draggable",
+    "enterkeyhint",
+    "hidden",
+    "id",
+    "inert",
+    "inputmode",
+    "is",
+    "itemid",
+    "itemprop",
+    "itemref",
+    "itemscope",
+    "itemtype",
+    "lang",
+    "nonce",
+    "part",
+    "popover",
+    "role",
+    "slot",
+    "spellcheck",
+    "style",
+    "tabindex",
+    "title",
+    "translate",
+    // element specific attributes
+    "abbr",
+    "accept",
+    "accept-charset",
+    "action",
+    "allow",
+    "allowfullscreen",
+    "alt",
+    "as",
+    "async",
+    "autocomplete",
+    "autoplay",
+    "blocking",
+    "charset",
+    "checked",
+    "cite",
+    "cols",
+    "colspan",
+    "content",
+    "controls",
+    "coords",
+    "crossorigin",
+    "data",
+    "datetime",
+    "decoding",
+    "default",
+    "defer",
+    "dirname",
+    "disabled",
+    "download",
+    "enctype",
+    "fetchpriority",
+    "for",
+    "form",
+    "formaction",
+    "formenctype",
+    "formmethod",
+    "formnovalidate",
+    "formtarget",
+    "headers",
+    "height",
+    "high",
+    "href",
+    "hreflang",
+    "http-equiv",
+    "imagesizes",
+    "imagesrcset",
+    "integrity",
+    "ismap",
+    "kind",
+    "label",
+    "list",
+    "loading",
+    "loop",
+    "low",
+    "max",
+    "maxlength",
+    "media",
+    "method",
+    "min",
+    "minlength",
+    "multiple",
+    "muted",
+    "name",
+    "nomodule",
+    "novalidate",
+    "open",
+    "optimum",
+    "pattern",
+    "ping",
+    "placeholder",
+    "playsinline",
+    "popovertarget",
+    "popovertargetaction",
+    "poster",
+    "preload",
+    "readonly",
+    "referrerpolicy",
+    "rel",
+    "required",
+    "reversed",
+    "rows",
+    "rowspan",
+    "sandbox",
+    "scope",
+    "selected",
+    "shape",
+    "size",
+    "sizes",
+    "span",
+    "src",
+    "srcdoc",
+    "srclang",
+    "srcset",
+    "start",
+    "step",
+    "target",
+    "type",
+    "usemap",
+    "value",
+    "width",
+    "wrap",
+    // event handler attributes
+    "onabort",
+    "onblur",
+    "oncancel",
+    "oncanplay",
+    "oncanplaythrough",
+    "onchange",
+    "onclick",
+    "onclose",
+    "oncontextmenu",
+    "oncopy",
+    "oncuechange",
+    "oncut",
+    "ondblclick",
+    "ondrag",
+    "ondragend",
+    "ondragenter",
+    "ondragleave",
+    "ondragover",
+    "ondragstart",
+    "ondrop",
+    "ondurationchange",
+    "onemptied",
+    "onended",
+    "onerror",
+    "onfocus",
+    "onfocusin",
+    "onfocusout",
+    "oninput",
+    "oninvalid",
+    "onkeydown",
+    "onkeypress",
+    "onkeyup",
+    "onload",
+    "onloadeddata",
+    "onloadedmetadata",
+    "onloadstart",
+    "onmousedown",
+    "onmouseenter",
+    "onmouseleave",
+    "onmousemove",
+    "onmouseout",
+    "onmouseover",
+    "onmouseup",
+    "onpaste",
+    "onpause",
+    "onplay",
+    "onplaying",
+    "onpointerdown",
+    "onpointerenter",
+    "onpointerleave",
+    "onpointermove",
+    "onpointerout",
+    "onpointerover",
+    "onpointerup",
+    "onprogress",
+    "onratechange",
+    "onreset",
+    "onresize",
+    "onscroll",
+    "onseeked",
+    "onseeking",
+    "onselect",
+    "onstalled",
+    "onsubmit",
+    "onsuspend",
+    "ontimeupdate",
+    "ontoggle",
+    "onvolumechange",
+    "onwaiting",
+    "onwheel",
+];
+
+const SVG_ATTRS: &[&str] = &[
+    "accumulate",
+    "additive",
+    "alignment-baseline",
+    "amplitude",
+    "attributeName",
+    "azimuth",
+    "baseFrequency",
+    "baseline-shift",
+    "begin",
+    "bias",
+    "by",
+    "calcMode",
+    "clip",
+    "clip-path",
+    "clip-rule",
+    "clipPathUnits",
+    "color",
+    "color-interpolation",
+    "color-interpolation-filters",
+    "cursor",
+    "cx",
+    "cy",
+    "d",
+    "diffuseConstant",
+    "direction",
+    "display",
+    "divisor",
+    "dominant-baseline",
+    "dur",
+    "dx",
+    "dy",
+    "edgeMode",
+    "elevation",
+    "end",
+    "exponent",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "filterUnits",
+    "flood-color",
+    "flood-opacity",
+    "font-family",
+    "font-size",
+    "font-size-adjust",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-weight",
+    "fr",
+    "from",
+    "fx",
+    "fy",
+    "gradientTransform",
+    "gradientUnits",
+    "image-rendering",
+    "in",
+    "in2",
+    "intercept",
+    "k1",
+    "k2",
+    "k3",
+    "k4",
+    "kernelMatrix",
+    "kernelUnitLength",
+    "keyPoints",
+    "keySplines",
+    "keyTimes",
+    "lengthAdjust",
+    "letter-spacing",
+    "lighting-color",
+    "limitingConeAngle",
+    "marker-end",
+    "marker-mid",
+    "marker-start",
+    "markerHeight",
+    "markerUnits",
+    "markerWidth",
+    "mask",
+    "maskContentUnits",
+    "maskUnits",
+    "mode",
+    "numOctaves",
+    "offset",
+    "opacity",
+    "operator",
+    "order",
+    "orient",
+    "origin",
+    "overflow",
+    "paint-order",
+    "path",
+    "pathLength",
+    "patternContentUnits",
+    "patternTransform",
+    "patternUnits",
+    "pointer-events",
+    "points",
+    "pointsAtX",
+    "pointsAtY",
+    "pointsAtZ",
+    "preserveAlpha",
+    "preserveAspectRatio",
+    "primitiveUnits",
+    "r",
+    "radius",
+    "refX",
+    "refY",
+    "repeatCount",
+    "repeatDur",
+    "restart",
+    "result",
+    "rotate",
+    "rx",
+    "ry",
+    "scale",
+    "seed",
+    "shape-rendering",
+    "side",
+    "spacing",
+    "specularConstant",
+    "specularExponent",
+    "spreadMethod",
+    "startOffset",
+    "stdDeviation",
+    "stitchTiles",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "surfaceScale",
+    "systemLanguage",
+    "tableValues",
+    "targetX",
+    "targetY",
+    "text-anchor",
+    "text-decoration",
+    "text-rendering",
+    "textLength",
+    "to",
+    "transform",
+    "transform-origin",
+    "unicode-bidi",
+    "values",
+    "vector-effect",
+    "version",
+    "viewBox",
+    "visibility",
+    "word-spacing",
+    "writing-mode",
+    "x",
+    "x1",
+    "x2",
+    "xChannelSelector",
+    "xmlns",
+    "y",
+    "y1",
+    "y2",
+    "yChannelSelector",
+    "z",
+    "zoomAndPan",
+];
+
+const MATHML_TAGS: &[&str] = &[
+    "annotation",
+    "annotation-xml",
+    "maction",
+    "math",
+    "menclose",
+    "merror",
+    "mfenced",
+    "mfrac",
+    "mi",
+    "mmultiscripts",
+    "mn",
+    "mo",
+    "mover",
+    "mpadded",
+    "mphantom",
+    "mprescripts",
+    "mroot",
+    "mrow",
+    "ms",
+    "mspace",
+    "msqrt",
+    "mstyle",
+    "msub",
+    "msubsup",
+    "msup",
+    "mtable",
+    "mtd",
+    "mtext",
+    "mtr",
+    "munder",
+    "munderover",
+    "semantics",
+];
+
+/// Attributes in the XLink, XML and XMLNS namespaces, written with their usual prefix.
+const XML_ATTRS: &[&str] = &[
+    "xlink:actuate",
+    "xlink:arcrole",
+    "xlink:href",
+    "xlink:role",
+    "xlink:show",
+    "xlink:title",
+    "xlink:type",
+    "xml:base",
+    "xml:lang",
+    "xml:space",
+    "xmlns:xlink",
+];
@@ -0,0 +1,9 @@
+use vdom::driver::Driver;
+use vdom::vdom::node::Node;
+use vdom_macro::html;
+
+fn view<D: Driver>() -> impl Node<D> {
+    html! { div { paragraph "text" } }
+}
+
+fn main() {}
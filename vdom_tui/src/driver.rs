@@ -0,0 +1,1427 @@
+use crate::{
+    frame::Frame,
+    layout::{ElementStyle, LayoutTree, NodeId, NodeKind},
+    Error,
+};
+use futures::{
+    future::LocalFutureObj,
+    task::{LocalSpawn, LocalSpawnExt, SpawnError},
+    FutureExt,
+};
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    rc::{Rc, Weak},
+};
+use vdom::{
+    driver::{Driver, DriverCtx},
+    program::ProgramRoot,
+    vdom::{
+        attr::{Attr, AttrDiffer, AttrValue, AttrVisitor, ClassList, NodeRef, SignalAttr, Style},
+        node::{
+            Comment, CommentStatic, Comp, CompNode, Node, NodeDiffer, NodeVisitor, Portal,
+            PortalTarget, RawHtml, SignalText, Suspense, Tag, Text,
+        },
+        signal::ReadSignal,
+    },
+};
+
+/// Mounts nodes into a `LayoutTree`, to be drawn on a terminal.
+///
+/// Of the attributes, only `id` and the style properties listed in `ElementStyle::from_props`
+/// have an effect. Raw HTML isn't supported.
+pub struct TuiDriver {
+    tree: LayoutTree,
+    spawner: Box<dyn LocalSpawn>,
+    /// Refreshes the dirty components of the app, set when the app is mounted.
+    refresh: Option<Rc<dyn Fn()>>,
+    is_refresh_spawned: bool,
+    error_handler: Option<Rc<dyn Fn(Error)>>,
+}
+
+impl TuiDriver {
+    /// Spawns the futures of the app, e.g. of its components, with `spawner`.
+    pub fn new<S>(spawner: S) -> TuiDriver
+    where
+        S: LocalSpawn + 'static,
+    {
+        TuiDriver {
+            tree: LayoutTree::new(),
+            spawner: Box::new(spawner),
+            refresh: None,
+            is_refresh_spawned: false,
+            error_handler: None,
+        }
+    }
+
+    /// Handles the errors of spawned futures, instead of printing them to stderr.
+    pub fn set_error_handler<F>(&mut self, f: F)
+    where
+        F: Fn(Error) + 'static,
+    {
+        self.error_handler = Some(Rc::new(f));
+    }
+
+    pub fn tree(&self) -> &LayoutTree {
+        &self.tree
+    }
+}
+
+#[derive(Default)]
+pub struct AttrStore;
+
+#[derive(Default)]
+pub struct TagStore {
+    node: Option<NodeId>,
+}
+
+#[derive(Default)]
+pub struct TextStore {
+    node: Option<NodeId>,
+}
+
+#[derive(Default)]
+pub struct CommentStore {
+    node: Option<NodeId>,
+}
+
+#[derive(Default)]
+pub struct CompStore;
+
+#[derive(Default)]
+pub struct PortalStore {
+    container: Option<NodeId>,
+}
+
+#[derive(Default)]
+pub struct RawHtmlStore;
+
+impl Driver for TuiDriver {
+    type AttrStore = AttrStore;
+    type TagStore = TagStore;
+    type TextStore = TextStore;
+    type CommentStore = CommentStore;
+    type CompStore = CompStore;
+    type PortalStore = PortalStore;
+    type RawHtmlStore = RawHtmlStore;
+    type Element = NodeId;
+    type Err = Error;
+
+    fn new_attr_store() -> AttrStore {
+        Default::default()
+    }
+
+    fn new_tag_store() -> TagStore {
+        Default::default()
+    }
+
+    fn new_text_store() -> TextStore {
+        Default::default()
+    }
+
+    fn new_comment_store() -> CommentStore {
+        Default::default()
+    }
+
+    fn new_comp_store() -> CompStore {
+        Default::default()
+    }
+
+    fn new_portal_store() -> PortalStore {
+        Default::default()
+    }
+
+    fn new_raw_html_store() -> RawHtmlStore {
+        Default::default()
+    }
+
+    fn request_render(&mut self) {
+        if self.is_refresh_spawned {
+            return;
+        }
+        if let Some(refresh) = self.refresh.clone() {
+            // retried on the next request if it can't be spawned
+            self.is_refresh_spawned = self
+                .spawn_local(futures::future::ready(()).map(move |_| refresh()))
+                .is_ok();
+        }
+    }
+
+    fn error_handler(&self) -> Rc<dyn Fn(Error)> {
+        match &self.error_handler {
+            Some(error_handler) => error_handler.clone(),
+            None => Rc::new(|err| eprintln!("{:?}", err)),
+        }
+    }
+}
+
+impl LocalSpawn for TuiDriver {
+    fn spawn_local_obj(&mut self, future: LocalFutureObj<'static, ()>) -> Result<(), SpawnError> {
+        self.spawner.spawn_local_obj(future)
+    }
+
+    fn status_local(&self) -> Result<(), SpawnError> {
+        self.spawner.status_local()
+    }
+}
+
+/// A node mounted into a terminal of a fixed width.
+///
+/// The app doesn't write to the terminal itself: `draw` returns the escape sequences updating the
+/// screen, e.g. to be written to stdout after running the executor.
+pub struct App<N>
+where
+    N: Node<TuiDriver>,
+{
+    state: Rc<RefCell<AppState<N>>>,
+}
+
+impl<N> App<N>
+where
+    N: Node<TuiDriver> + 'static,
+{
+    pub fn new(mut node: N, driver: TuiDriver, width: usize) -> Result<App<N>, Error> {
+        assert!(width > 0, "width must be at least 1");
+        let driver_ctx = DriverCtx::new(driver);
+        let root = driver_ctx.with(|drv| drv.tree.root());
+        node.visit(
+            &mut 0,
+            &mut NodeAddVisitor {
+                driver_ctx: &driver_ctx,
+                parent: root,
+            },
+        )?;
+        let state = Rc::new(RefCell::new(AppState {
+            driver_ctx: driver_ctx.clone(),
+            node,
+            width,
+            drawn: None,
+        }));
+        let weak_state = Rc::downgrade(&state);
+        driver_ctx.with_mut(|drv| drv.refresh = Some(Rc::new(move || refresh(&weak_state))));
+        Ok(App { state })
+    }
+
+    pub fn set(&mut self, mut node: N) -> Result<(), Error> {
+        let state = &mut *self.state.borrow_mut();
+        let root = state.driver_ctx.with(|drv| drv.tree.root());
+        node.diff(
+            &mut 0,
+            &mut 0,
+            &mut state.node,
+            &mut NodeStdDiffer {
+                driver_ctx: &state.driver_ctx,
+                parent: root,
+            },
+        )?;
+        state.node = node;
+        Ok(())
+    }
+
+    pub fn driver_ctx(&self) -> DriverCtx<TuiDriver> {
+        self.state.borrow().driver_ctx.clone()
+    }
+
+    /// Lays out the mounted nodes.
+    pub fn frame(&self) -> Frame {
+        let state = self.state.borrow();
+        state.driver_ctx.with(|drv| drv.tree.render(state.width))
+    }
+
+    /// The escape sequences bringing the screen up to date: the whole frame on the first call,
+    /// only the cells that changed since the previous call afterwards.
+    pub fn draw(&mut self) -> String {
+        let frame = self.frame();
+        let state = &mut *self.state.borrow_mut();
+        let ansi = match &state.drawn {
+            Some(drawn) => frame.ansi_diff(drawn),
+            None => frame.ansi(),
+        };
+        state.drawn = Some(frame);
+        ansi
+    }
+}
+
+/// Lets an `App` host a `Program`, e.g.
+/// `ProgramRunner::new(program, |view| App::new(view, TuiDriver::new(spawner), 80))`.
+impl<N> ProgramRoot<TuiDriver, N> for App<N>
+where
+    N: Node<TuiDriver> + 'static,
+{
+    type Err = Error;
+
+    fn driver_ctx(&self) -> DriverCtx<TuiDriver> {
+        App::driver_ctx(self)
+    }
+
+    fn set(&mut self, node: N) -> Result<(), Error> {
+        App::set(self, node)
+    }
+}
+
+struct AppState<N> {
+    driver_ctx: DriverCtx<TuiDriver>,
+    node: N,
+    width: usize,
+    /// The frame on the screen, as of the last `App::draw`.
+    drawn: Option<Frame>,
+}
+
+/// Renders the components that requested it again, if the app is still around.
+fn refresh<N>(state: &Weak<RefCell<AppState<N>>>)
+where
+    N: Node<TuiDriver>,
+{
+    let state = match state.upgrade() {
+        Some(state) => state,
+        None => return,
+    };
+    let (driver_ctx, res) = {
+        let state = &mut *state.borrow_mut();
+        let root = state.driver_ctx.with_mut(|drv| {
+            drv.is_refresh_spawned = false;
+            drv.tree.root()
+        });
+        let res = state.node.visit(
+            &mut 0,
+            &mut NodeRefreshVisitor {
+                driver_ctx: &state.driver_ctx,
+                parent: root,
+            },
+        );
+        (state.driver_ctx.clone(), res)
+    };
+    // reported after releasing the app, which the error handler may use
+    if let Err(err) = res {
+        driver_ctx.report_error(err);
+    }
+}
+
+struct NodeAddVisitor<'a> {
+    driver_ctx: &'a DriverCtx<TuiDriver>,
+    parent: NodeId,
+}
+
+impl<'a> NodeVisitor<TuiDriver> for NodeAddVisitor<'a> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<TuiDriver>,
+    {
+        let node = self
+            .driver_ctx
+            .with_mut(|drv| drv.tree.create_element(tag.tag()));
+        tag.visit_attrs(&mut AttrAddVisitor {
+            driver_ctx: self.driver_ctx,
+            node,
+        })?;
+        tag.visit_children(&mut NodeAddVisitor {
+            driver_ctx: self.driver_ctx,
+            parent: node,
+        })?;
+        self.driver_ctx
+            .with_mut(|drv| drv.tree.insert(self.parent, index, node))?;
+        tag.driver_store().node = Some(node);
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), Error>
+    where
+        T: Text<TuiDriver>,
+    {
+        let node = self.insert(index, NodeKind::Text(text.get().to_owned()))?;
+        text.driver_store().node = Some(node);
+        Ok(())
+    }
+
+    fn on_comment<C>(&mut self, index: usize, comment: &mut C) -> Result<(), Error>
+    where
+        C: Comment<TuiDriver>,
+    {
+        let node = self.insert(index, NodeKind::Comment(comment.get().to_owned()))?;
+        comment.driver_store().node = Some(node);
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<TuiDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<TuiDriver>,
+    {
+        comp.init_comp_ctx(self.driver_ctx.clone());
+        comp.visit_rendered(index, self)
+    }
+
+    fn on_portal<N>(&mut self, portal: &mut Portal<TuiDriver, N>) -> Result<(), Error>
+    where
+        N: Node<TuiDriver>,
+    {
+        // an inline container, the children flow along with the ones of the target
+        let container = self
+            .driver_ctx
+            .with_mut(|drv| drv.tree.create_element("span"));
+        portal.visit_children(&mut NodeAddVisitor {
+            driver_ctx: self.driver_ctx,
+            parent: container,
+        })?;
+        self.driver_ctx.with_mut(|drv| {
+            let target = target_node(&drv.tree, portal.target())?;
+            drv.tree.append(target, container)
+        })?;
+        portal.driver_store().container = Some(container);
+        Ok(())
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _index: usize,
+        _raw_html: &mut RawHtml<TuiDriver>,
+    ) -> Result<(), Error> {
+        Err("raw HTML isn't supported".into())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        index: usize,
+        text: &mut SignalText<TuiDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        self.on_text(index, text)?;
+        bind_text(self.driver_ctx, text)
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<TuiDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<TuiDriver> + 'static,
+        Fb: Node<TuiDriver> + 'static,
+    {
+        spawn_suspense(self.driver_ctx, suspense)?;
+        suspense.visit_content(index, self)
+    }
+}
+
+impl<'a> NodeAddVisitor<'a> {
+    fn insert(&self, index: usize, kind: NodeKind) -> Result<NodeId, Error> {
+        self.driver_ctx.with_mut(|drv| {
+            let node = drv.tree.create(kind);
+            drv.tree.insert(self.parent, index, node)?;
+            Ok(node)
+        })
+    }
+}
+
+/// Looks for dirty components in a mounted tree, diffing their new render against the old one.
+struct NodeRefreshVisitor<'a> {
+    driver_ctx: &'a DriverCtx<TuiDriver>,
+    parent: NodeId,
+}
+
+impl<'a> NodeVisitor<TuiDriver> for NodeRefreshVisitor<'a> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<TuiDriver>,
+    {
+        let node = tag.driver_store().node.ok_or("node is None")?;
+        tag.visit_children(&mut NodeRefreshVisitor {
+            driver_ctx: self.driver_ctx,
+            parent: node,
+        })
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Error>
+    where
+        T: Text<TuiDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comment<C>(&mut self, _index: usize, _comment: &mut C) -> Result<(), Error>
+    where
+        C: Comment<TuiDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<TuiDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<TuiDriver>,
+    {
+        if comp.is_dirty() {
+            comp.refresh_rendered(
+                index,
+                &mut NodeStdDiffer {
+                    driver_ctx: self.driver_ctx,
+                    parent: self.parent,
+                },
+            )
+        } else {
+            comp.visit_rendered(index, self)
+        }
+    }
+
+    fn on_portal<N>(&mut self, portal: &mut Portal<TuiDriver, N>) -> Result<(), Error>
+    where
+        N: Node<TuiDriver>,
+    {
+        let container = portal.driver_store().container.ok_or("container is None")?;
+        portal.visit_children(&mut NodeRefreshVisitor {
+            driver_ctx: self.driver_ctx,
+            parent: container,
+        })
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _index: usize,
+        _raw_html: &mut RawHtml<TuiDriver>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        _index: usize,
+        _text: &mut SignalText<TuiDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        Ok(())
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<TuiDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<TuiDriver> + 'static,
+        Fb: Node<TuiDriver> + 'static,
+    {
+        suspense.visit_content(index, self)
+    }
+}
+
+/// Removes the nodes from the tree.
+///
+/// Walks the whole subtree to clear its `NodeRef`s, but only removes its root nodes, which frees
+/// their descendants.
+struct NodeRemoveVisitor<'a> {
+    driver_ctx: &'a DriverCtx<TuiDriver>,
+    is_detached: bool,
+}
+
+impl<'a> NodeRemoveVisitor<'a> {
+    fn remove(&self, node: Option<NodeId>) -> Result<(), Error> {
+        if self.is_detached {
+            return Ok(());
+        }
+        let node = node.ok_or("node is None")?;
+        self.driver_ctx.with_mut(|drv| drv.tree.remove(node))
+    }
+}
+
+impl<'a> NodeVisitor<TuiDriver> for NodeRemoveVisitor<'a> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<TuiDriver>,
+    {
+        tag.visit_attrs(&mut NodeRefClearVisitor)?;
+        tag.visit_children(&mut NodeRemoveVisitor {
+            driver_ctx: self.driver_ctx,
+            is_detached: true,
+        })?;
+        self.remove(tag.driver_store().node)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Error>
+    where
+        T: Text<TuiDriver>,
+    {
+        self.remove(text.driver_store().node)
+    }
+
+    fn on_comment<C>(&mut self, _index: usize, comment: &mut C) -> Result<(), Error>
+    where
+        C: Comment<TuiDriver>,
+    {
+        self.remove(comment.driver_store().node)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<TuiDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<TuiDriver>,
+    {
+        comp.visit_rendered(index, self)
+    }
+
+    /// The container of a portal lives outside of the subtree, so it is always removed.
+    fn on_portal<N>(&mut self, portal: &mut Portal<TuiDriver, N>) -> Result<(), Error>
+    where
+        N: Node<TuiDriver>,
+    {
+        portal.visit_children(&mut NodeRemoveVisitor {
+            driver_ctx: self.driver_ctx,
+            is_detached: true,
+        })?;
+        let container = portal.driver_store().container.ok_or("container is None")?;
+        self.driver_ctx.with_mut(|drv| drv.tree.remove(container))
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _index: usize,
+        _raw_html: &mut RawHtml<TuiDriver>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        index: usize,
+        text: &mut SignalText<TuiDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        // the binding is dropped along with the node
+        self.on_text(index, text)
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<TuiDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<TuiDriver> + 'static,
+        Fb: Node<TuiDriver> + 'static,
+    {
+        suspense.visit_content(index, self)
+    }
+}
+
+struct NodeRefClearVisitor;
+
+impl AttrVisitor<TuiDriver> for NodeRefClearVisitor {
+    type Err = Error;
+
+    fn on_attr<A>(&mut self, _attr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<TuiDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_style(&mut self, _style: &mut Style<TuiDriver>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_class_list(&mut self, _class_list: &mut ClassList<TuiDriver>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_node_ref(&mut self, node_ref: &mut NodeRef<TuiDriver>) -> Result<(), Error> {
+        node_ref.set(None);
+        Ok(())
+    }
+
+    fn on_signal_attr<S>(&mut self, _attr: &mut SignalAttr<TuiDriver, S>) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>,
+    {
+        Ok(())
+    }
+}
+
+struct AttrAddVisitor<'a> {
+    driver_ctx: &'a DriverCtx<TuiDriver>,
+    node: NodeId,
+}
+
+impl<'a> AttrVisitor<TuiDriver> for AttrAddVisitor<'a> {
+    type Err = Error;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<TuiDriver>,
+    {
+        if attr.name() == "id" {
+            let id = attr.value().to_str("id");
+            self.driver_ctx
+                .with_mut(|drv| drv.tree.set_id(self.node, id.as_ref().map(AsRef::as_ref)))?;
+        }
+        Ok(())
+    }
+
+    fn on_style(&mut self, style: &mut Style<TuiDriver>) -> Result<(), Error> {
+        let style = ElementStyle::from_props(style.iter());
+        self.driver_ctx
+            .with_mut(|drv| drv.tree.set_style(self.node, style))
+    }
+
+    fn on_class_list(&mut self, _class_list: &mut ClassList<TuiDriver>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_node_ref(&mut self, node_ref: &mut NodeRef<TuiDriver>) -> Result<(), Error> {
+        node_ref.set(Some(self.node));
+        Ok(())
+    }
+
+    fn on_signal_attr<S>(&mut self, attr: &mut SignalAttr<TuiDriver, S>) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>,
+    {
+        self.on_attr(attr)?;
+        bind_attr(self.driver_ctx, self.node, attr);
+        Ok(())
+    }
+}
+
+struct NodeStdDiffer<'a> {
+    driver_ctx: &'a DriverCtx<TuiDriver>,
+    parent: NodeId,
+}
+
+impl<'a> NodeDiffer<TuiDriver> for NodeStdDiffer<'a> {
+    type Err = Error;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Error>
+    where
+        N: Node<TuiDriver>,
+    {
+        curr.visit(
+            index,
+            &mut NodeAddVisitor {
+                driver_ctx: self.driver_ctx,
+                parent: self.parent,
+            },
+        )
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Error>
+    where
+        N: Node<TuiDriver>,
+    {
+        ancestor.visit(
+            ancestor_index,
+            &mut NodeRemoveVisitor {
+                driver_ctx: self.driver_ctx,
+                is_detached: false,
+            },
+        )
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Error>
+    where
+        T: Tag<TuiDriver>,
+    {
+        let node = ancestor.driver_store().node.take().ok_or("node is None")?;
+        if curr.tag() == ancestor.tag() {
+            curr.diff_attrs(
+                ancestor,
+                &mut AttrStdDiffer {
+                    driver_ctx: self.driver_ctx,
+                    node,
+                },
+            )?;
+        } else {
+            // the node is kept along with its children, only its attributes are set again
+            self.driver_ctx
+                .with_mut(|drv| drv.tree.set_tag(node, curr.tag()))?;
+            curr.visit_attrs(&mut AttrAddVisitor {
+                driver_ctx: self.driver_ctx,
+                node,
+            })?;
+        }
+        curr.diff_children(
+            ancestor,
+            &mut NodeStdDiffer {
+                driver_ctx: self.driver_ctx,
+                parent: node,
+            },
+        )?;
+        curr.driver_store().node = Some(node);
+        Ok(())
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Error>
+    where
+        T: Text<TuiDriver>,
+    {
+        let node = ancestor.driver_store().node.take().ok_or("node is None")?;
+        if curr.get() != ancestor.get() {
+            self.driver_ctx
+                .with_mut(|drv| drv.tree.set_text(node, curr.get()))?;
+        }
+        curr.driver_store().node = Some(node);
+        Ok(())
+    }
+
+    fn on_comment<C>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut C,
+        ancestor: &mut C,
+    ) -> Result<(), Error>
+    where
+        C: Comment<TuiDriver>,
+    {
+        let node = ancestor.driver_store().node.take().ok_or("node is None")?;
+        if curr.get() != ancestor.get() {
+            self.driver_ctx
+                .with_mut(|drv| drv.tree.set_text(node, curr.get()))?;
+        }
+        curr.driver_store().node = Some(node);
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<TuiDriver, C>,
+        ancestor: &mut CompNode<TuiDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<TuiDriver>,
+    {
+        if curr.comp_ctx().is_none() {
+            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
+            curr.set_comp_ctx(ctx.clone());
+        }
+        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+    }
+
+    fn on_portal<N>(
+        &mut self,
+        curr: &mut Portal<TuiDriver, N>,
+        ancestor: &mut Portal<TuiDriver, N>,
+    ) -> Result<(), Error>
+    where
+        N: Node<TuiDriver>,
+    {
+        let container = ancestor
+            .driver_store()
+            .container
+            .take()
+            .ok_or("container is None")?;
+        if curr.target() != ancestor.target() {
+            // appending moves the container, along with all the children
+            self.driver_ctx.with_mut(|drv| {
+                let target = target_node(&drv.tree, curr.target())?;
+                drv.tree.append(target, container)
+            })?;
+        }
+        curr.diff_children(
+            ancestor,
+            &mut NodeStdDiffer {
+                driver_ctx: self.driver_ctx,
+                parent: container,
+            },
+        )?;
+        curr.driver_store().container = Some(container);
+        Ok(())
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        _curr: &mut RawHtml<TuiDriver>,
+        _ancestor: &mut RawHtml<TuiDriver>,
+    ) -> Result<(), Error> {
+        Err("raw HTML isn't supported".into())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut SignalText<TuiDriver, S>,
+        ancestor: &mut SignalText<TuiDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        let node = ancestor.driver_store().node.take().ok_or("node is None")?;
+        let is_bound = curr.adopt_binding(ancestor);
+        if !is_bound {
+            // the text of the ancestor may be outdated, the binding patched the node since
+            self.driver_ctx
+                .with_mut(|drv| drv.tree.set_text(node, curr.get()))?;
+        }
+        curr.driver_store().node = Some(node);
+        if !is_bound {
+            bind_text(self.driver_ctx, curr)?;
+        }
+        Ok(())
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut Suspense<TuiDriver, N, Fb>,
+        ancestor: &mut Suspense<TuiDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<TuiDriver> + 'static,
+        Fb: Node<TuiDriver> + 'static,
+    {
+        curr.diff_content(curr_index, ancestor_index, ancestor, self)?;
+        spawn_suspense(self.driver_ctx, curr)
+    }
+}
+
+struct AttrStdDiffer<'a> {
+    driver_ctx: &'a DriverCtx<TuiDriver>,
+    node: NodeId,
+}
+
+impl<'a> AttrStdDiffer<'a> {
+    fn add_visitor(&self) -> AttrAddVisitor<'a> {
+        AttrAddVisitor {
+            driver_ctx: self.driver_ctx,
+            node: self.node,
+        }
+    }
+}
+
+impl<'a> AttrDiffer<TuiDriver> for AttrStdDiffer<'a> {
+    type Err = Error;
+
+    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Error>
+    where
+        A: Attr<TuiDriver>,
+    {
+        if curr.value() == ancestor.value() {
+            return Ok(());
+        }
+        self.add_visitor().on_attr(curr)
+    }
+
+    fn on_attr_added<A>(&mut self, curr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<TuiDriver>,
+    {
+        self.add_visitor().on_attr(curr)
+    }
+
+    fn on_attr_removed<A>(&mut self, ancestor: &mut A) -> Result<(), Error>
+    where
+        A: Attr<TuiDriver>,
+    {
+        if ancestor.name() == "id" {
+            self.driver_ctx
+                .with_mut(|drv| drv.tree.set_id(self.node, None))?;
+        }
+        Ok(())
+    }
+
+    fn on_style(
+        &mut self,
+        curr: &mut Style<TuiDriver>,
+        ancestor: &mut Style<TuiDriver>,
+    ) -> Result<(), Error> {
+        if curr.changes(ancestor).next().is_none() {
+            return Ok(());
+        }
+        self.add_visitor().on_style(curr)
+    }
+
+    fn on_class_list(
+        &mut self,
+        _curr: &mut ClassList<TuiDriver>,
+        _ancestor: &mut ClassList<TuiDriver>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_node_ref(
+        &mut self,
+        curr: &mut NodeRef<TuiDriver>,
+        ancestor: &mut NodeRef<TuiDriver>,
+    ) -> Result<(), Error> {
+        if !curr.ptr_eq(ancestor) {
+            ancestor.set(None);
+            curr.set(Some(self.node));
+        }
+        Ok(())
+    }
+
+    fn on_signal_attr<S>(
+        &mut self,
+        curr: &mut SignalAttr<TuiDriver, S>,
+        ancestor: &mut SignalAttr<TuiDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>,
+    {
+        if curr.adopt_binding(ancestor) {
+            return Ok(());
+        }
+        // the value of the ancestor may be outdated, the binding patched the attribute since
+        self.add_visitor().on_signal_attr(curr)
+    }
+}
+
+/// Spawns the future of the suspense, if it wasn't yet, to resolve it once done.
+fn spawn_suspense<N, Fb>(
+    driver_ctx: &DriverCtx<TuiDriver>,
+    suspense: &mut Suspense<TuiDriver, N, Fb>,
+) -> Result<(), Error>
+where
+    N: Node<TuiDriver> + 'static,
+    Fb: Node<TuiDriver> + 'static,
+{
+    if let Some(future) = suspense.take_future() {
+        let handle = suspense.handle();
+        let resolve_ctx = driver_ctx.clone();
+        driver_ctx.with_mut(|drv| {
+            drv.spawn_local(future.map(move |node| {
+                let res = handle.resolve(node, |anchor, fallback, node| {
+                    resolve_suspense(&resolve_ctx, anchor, fallback, node)
+                });
+                if let Some(Err(err)) = res {
+                    resolve_ctx.report_error(err);
+                }
+            }))
+        })?;
+    }
+    Ok(())
+}
+
+/// Replaces the fallback of a suspense with the resolved node, right after the anchor.
+fn resolve_suspense<N, Fb>(
+    driver_ctx: &DriverCtx<TuiDriver>,
+    anchor: &mut CommentStatic<TuiDriver>,
+    fallback: &mut Fb,
+    node: &mut N,
+) -> Result<(), Error>
+where
+    N: Node<TuiDriver>,
+    Fb: Node<TuiDriver>,
+{
+    let anchor = anchor.driver_store().node.ok_or("node is None")?;
+    let (parent, anchor_index) = driver_ctx.with(|drv| drv.tree.index_of(anchor))?;
+    fallback.visit(
+        &mut (anchor_index + 1),
+        &mut NodeRemoveVisitor {
+            driver_ctx,
+            is_detached: false,
+        },
+    )?;
+    node.visit(
+        &mut (anchor_index + 1),
+        &mut NodeAddVisitor { driver_ctx, parent },
+    )
+}
+
+fn target_node(tree: &LayoutTree, target: &PortalTarget<TuiDriver>) -> Result<NodeId, Error> {
+    let node = match target {
+        PortalTarget::Element(node) => *node,
+        PortalTarget::Id(id) => tree.find_by_id(id).ok_or("portal target not found")?,
+    };
+    Ok(node)
+}
+
+/// Patches the text node on every change of the signal.
+fn bind_text<S>(
+    driver_ctx: &DriverCtx<TuiDriver>,
+    text: &mut SignalText<TuiDriver, S>,
+) -> Result<(), Error>
+where
+    S: ReadSignal,
+    S::Value: Display,
+{
+    let node = text.driver_store().node.ok_or("node is None")?;
+    let driver_ctx = driver_ctx.clone();
+    text.bind(move |value| {
+        if let Err(err) = driver_ctx.with_mut(|drv| drv.tree.set_text(node, value)) {
+            driver_ctx.report_error(err);
+        }
+    });
+    Ok(())
+}
+
+/// Patches the element on every change of the signal, if the attribute has any effect.
+fn bind_attr<S>(
+    driver_ctx: &DriverCtx<TuiDriver>,
+    node: NodeId,
+    attr: &mut SignalAttr<TuiDriver, S>,
+) where
+    S: ReadSignal,
+    S::Value: Clone + Into<AttrValue>,
+{
+    if attr.name() != "id" {
+        return;
+    }
+    let driver_ctx = driver_ctx.clone();
+    attr.bind(move |value| {
+        if let Err(err) = driver_ctx.with_mut(|drv| drv.tree.set_id(node, value)) {
+            driver_ctx.report_error(err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Color;
+    use futures::{channel::oneshot, executor::LocalPool};
+    use sink_cell::SinkCell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use vdom::vdom::{
+        attr::DisplayAttr,
+        node::{CompCtx, SignalText, TextDyn, TextStatic},
+        signal::Signal,
+    };
+    use vdom_macro::html;
+
+    fn mount<N>(pool: &LocalPool, node: N, width: usize) -> App<N>
+    where
+        N: Node<TuiDriver> + 'static,
+    {
+        App::new(node, TuiDriver::new(pool.spawner()), width).unwrap()
+    }
+
+    /// Runs the futures, including the ones spawned by them, which `run_until_stalled` leaves
+    /// for its next call.
+    fn run(pool: &mut LocalPool) {
+        pool.run_until_stalled();
+        pool.run_until_stalled();
+    }
+
+    #[test]
+    fn renders_blocks_and_lists() {
+        let pool = LocalPool::new();
+        let app = mount(
+            &pool,
+            html! {
+                div {
+                    p "Hello world"
+                    ul {
+                        li "one"
+                        li { b "two" " and " span "three" }
+                    }
+                    "done"
+                }
+            },
+            20,
+        );
+        assert_eq!(
+            app.frame().to_string(),
+            "Hello world\n\n• one\n• two and three\ndone"
+        );
+    }
+
+    #[test]
+    fn wraps_words_within_blocks() {
+        let pool = LocalPool::new();
+        let app = mount(
+            &pool,
+            html! {
+                ul {
+                    li "alpha beta gamma"
+                    li "abcdefghijkl"
+                }
+            },
+            10,
+        );
+        assert_eq!(
+            app.frame().to_string(),
+            "• alpha\n  beta\n  gamma\n• abcdefgh\n  ijkl"
+        );
+    }
+
+    #[test]
+    fn maps_styles() {
+        let pool = LocalPool::new();
+        let app = mount(
+            &pool,
+            html! {
+                div style:color="red" {
+                    "a"
+                    b "b"
+                    span style:background-color="blue" style:text-decoration="underline" "c"
+                }
+            },
+            10,
+        );
+        let frame = app.frame();
+        let style = |x| frame.get(x, 0).unwrap().style;
+        assert_eq!(style(0).fg, Some(Color::Red));
+        assert!(!style(0).bold);
+        assert!(style(1).bold);
+        assert_eq!(style(2).bg, Some(Color::Blue));
+        assert!(style(2).underline);
+        assert_eq!(style(2).fg, Some(Color::Red));
+    }
+
+    fn counter(count: u32) -> impl Node<TuiDriver> {
+        let items = if count > 1 {
+            Some(html! { ul { li "more" } })
+        } else {
+            None
+        };
+        html! {
+            div {
+                p "count: {count}"
+                (items)
+            }
+        }
+    }
+
+    #[test]
+    fn applies_diffs_as_cell_updates() {
+        let pool = LocalPool::new();
+        let mut app = mount(&pool, counter(1), 20);
+        assert!(app.draw().starts_with("\x1b[H\x1b[2J"));
+
+        app.set(counter(2)).unwrap();
+        let frame = app.frame();
+        assert_eq!(frame.to_string(), "count: 2\n\n• more");
+        assert_eq!(app.draw(), "\x1b[1;8H\x1b[0m2\x1b[3;1H•\x1b[3;3Hmore");
+
+        app.set(counter(1)).unwrap();
+        assert_eq!(app.frame().to_string(), "count: 1");
+        assert_eq!(app.draw(), "\x1b[1;8H\x1b[0m1\x1b[3;1H \x1b[3;3H    ");
+        assert_eq!(app.draw(), "");
+    }
+
+    fn label(on: bool) -> impl Node<TuiDriver> {
+        let text = TextStatic::new(if on { "on" } else { "off" });
+        html! { p { "state: " (text) } }
+    }
+
+    #[test]
+    fn patches_static_texts_picked_at_runtime() {
+        let pool = LocalPool::new();
+        let mut app = mount(&pool, label(true), 20);
+        app.set(label(false)).unwrap();
+        assert_eq!(app.frame().to_string(), "state: off");
+    }
+
+    #[test]
+    fn patches_signal_texts() {
+        let pool = LocalPool::new();
+        let count = Signal::new(1);
+        let text = SignalText::new(&count);
+        let app = mount(&pool, html! { div { "count: " (text) } }, 20);
+        count.set(42);
+        assert_eq!(app.frame().to_string(), "count: 42");
+    }
+
+    fn count(signal: &Signal<u32>) -> impl Node<TuiDriver> {
+        let text = SignalText::new(signal);
+        html! { div { "count: " (text) } }
+    }
+
+    #[test]
+    fn adopts_signal_bindings_on_diff() {
+        let pool = LocalPool::new();
+        let first = Signal::new(1);
+        let mut app = mount(&pool, count(&first), 20);
+        app.set(count(&first)).unwrap();
+        first.set(2);
+        assert_eq!(app.frame().to_string(), "count: 2");
+
+        // bound to another signal, the text isn't patched by the first one anymore
+        let second = Signal::new(10);
+        app.set(count(&second)).unwrap();
+        assert_eq!(app.frame().to_string(), "count: 10");
+        first.set(3);
+        assert_eq!(app.frame().to_string(), "count: 10");
+        second.set(11);
+        assert_eq!(app.frame().to_string(), "count: 11");
+    }
+
+    #[test]
+    fn mounts_portals_by_id() {
+        let pool = LocalPool::new();
+        let status = Portal::with_id("status", html! { b "ready" });
+        let app = mount(
+            &pool,
+            html! {
+                div {
+                    div id="status" { "status: " }
+                    "main"
+                    (status)
+                }
+            },
+            20,
+        );
+        assert_eq!(app.frame().to_string(), "status: ready\nmain");
+    }
+
+    static FORMATS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts how often it is formatted.
+    #[derive(PartialEq)]
+    struct ItemId(u32);
+
+    impl std::fmt::Display for ItemId {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            FORMATS.fetch_add(1, Ordering::SeqCst);
+            write!(f, "item-{}", self.0)
+        }
+    }
+
+    fn item(id: u32) -> impl Node<TuiDriver> {
+        let id = DisplayAttr::new("id", ItemId(id));
+        html! { p ..(id) { "item" } }
+    }
+
+    #[test]
+    fn formats_display_attrs_on_changes_only() {
+        let pool = LocalPool::new();
+        let mut app = mount(&pool, item(1), 20);
+        let has_id = |app: &App<_>, id| {
+            app.driver_ctx()
+                .with(|drv| drv.tree().find_by_id(id).is_some())
+        };
+        assert!(has_id(&app, "item-1"));
+
+        app.set(item(1)).unwrap();
+        assert_eq!(FORMATS.load(Ordering::SeqCst), 1);
+
+        app.set(item(2)).unwrap();
+        assert_eq!(FORMATS.load(Ordering::SeqCst), 2);
+        assert!(!has_id(&app, "item-1"));
+        assert!(has_id(&app, "item-2"));
+    }
+
+    fn loader(id: u32, data: oneshot::Receiver<&'static str>) -> impl Node<TuiDriver> {
+        let content = data.map(|data| {
+            let text = TextStatic::new(data.unwrap_or("canceled"));
+            html! { b { (text) } }
+        });
+        let suspense = Suspense::new(content, html! { i "loading" }).with_key(id);
+        html! { p { (suspense) } }
+    }
+
+    #[test]
+    fn keeps_suspense_with_the_same_key() {
+        let mut pool = LocalPool::new();
+        let (first_tx, first_rx) = oneshot::channel();
+        let mut app = mount(&pool, loader(1, first_rx), 20);
+
+        // the futures of later renders are dropped, they would resolve to "canceled" otherwise
+        app.set(loader(1, oneshot::channel().1)).unwrap();
+        run(&mut pool);
+        assert_eq!(app.frame().to_string(), "loading");
+        first_tx.send("first").unwrap();
+        run(&mut pool);
+        assert_eq!(app.frame().to_string(), "first");
+
+        app.set(loader(1, oneshot::channel().1)).unwrap();
+        run(&mut pool);
+        assert_eq!(app.frame().to_string(), "first");
+    }
+
+    #[test]
+    fn restarts_suspense_when_the_key_changes() {
+        let mut pool = LocalPool::new();
+        let (first_tx, first_rx) = oneshot::channel();
+        let mut app = mount(&pool, loader(1, first_rx), 20);
+        first_tx.send("first").unwrap();
+        run(&mut pool);
+        assert_eq!(app.frame().to_string(), "first");
+
+        // a new key once resolved shows the fallback until the new future resolves
+        let (second_tx, second_rx) = oneshot::channel();
+        app.set(loader(2, second_rx)).unwrap();
+        assert_eq!(app.frame().to_string(), "loading");
+
+        // a new key while pending replaces the future, the result of the previous one is dropped
+        let (third_tx, third_rx) = oneshot::channel();
+        app.set(loader(3, third_rx)).unwrap();
+        second_tx.send("second").unwrap();
+        run(&mut pool);
+        assert_eq!(app.frame().to_string(), "loading");
+        third_tx.send("third").unwrap();
+        run(&mut pool);
+        assert_eq!(app.frame().to_string(), "third");
+    }
+
+    /// Renders the value of its input cell, again on every change.
+    #[derive(Clone, PartialEq, Eq)]
+    struct Ticker;
+
+    impl Comp<TuiDriver> for Ticker {
+        type Input = SinkCell<u32>;
+        type Rendered = TextDyn<TuiDriver>;
+
+        fn new(input: &SinkCell<u32>, ctx: CompCtx<TuiDriver, Self>) -> Ticker {
+            ctx.subscribe(input.changes())
+                .expect("failed to subscribe to the input");
+            Ticker
+        }
+
+        fn render(&self, input: &SinkCell<u32>) -> TextDyn<TuiDriver> {
+            TextDyn::new(format!("ticks: {}", input.get()))
+        }
+    }
+
+    #[test]
+    fn refreshes_components() {
+        let mut pool = LocalPool::new();
+        let ticks = SinkCell::new(0);
+        let ticker = CompNode::<_, Ticker>::new(ticks.clone());
+        let mut app = mount(&pool, html! { p { (ticker) } }, 20);
+        app.draw();
+
+        ticks.set(3);
+        run(&mut pool);
+        assert_eq!(app.frame().to_string(), "ticks: 3");
+        assert_eq!(app.draw(), "\x1b[1;8H\x1b[0m3");
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt::{self, Write};
+
+/// The basic terminal colors, see `Color::from_css` for how they are named in styles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+}
+
+impl Color {
+    /// Parses a CSS color keyword, other colors aren't supported.
+    pub fn from_css(value: &str) -> Option<Color> {
+        let color = match value.trim().to_ascii_lowercase().as_str() {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" | "fuchsia" => Color::Magenta,
+            "cyan" | "aqua" => Color::Cyan,
+            "white" => Color::White,
+            "gray" | "grey" => Color::Gray,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// The SGR parameter, `base` being 30 for the foreground and 40 for the background.
+    fn sgr(self, base: u8) -> u8 {
+        match self {
+            Color::Black => base,
+            Color::Red => base + 1,
+            Color::Green => base + 2,
+            Color::Yellow => base + 3,
+            Color::Blue => base + 4,
+            Color::Magenta => base + 5,
+            Color::Cyan => base + 6,
+            Color::White => base + 7,
+            // the bright variant of black
+            Color::Gray => base + 60,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellStyle {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl CellStyle {
+    /// Writes the SGR sequence switching to this style, whatever the previous style was.
+    fn write_sgr(&self, out: &mut String) {
+        out.push_str("\x1b[0");
+        if self.bold {
+            out.push_str(";1");
+        }
+        if self.italic {
+            out.push_str(";3");
+        }
+        if self.underline {
+            out.push_str(";4");
+        }
+        if let Some(fg) = self.fg {
+            write!(out, ";{}", fg.sgr(30)).unwrap();
+        }
+        if let Some(bg) = self.bg {
+            write!(out, ";{}", bg.sgr(40)).unwrap();
+        }
+        out.push('m');
+    }
+}
+
+/// A single character of a frame, every character taking up one column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell {
+            ch: ' ',
+            style: CellStyle::default(),
+        }
+    }
+}
+
+/// A cell that changed between two frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellUpdate {
+    pub x: usize,
+    pub y: usize,
+    pub cell: Cell,
+}
+
+/// A grid of cells with a fixed width, as tall as its content.
+///
+/// Displayed as plain text, without styles and trailing spaces, e.g. to compare frames in tests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    pub fn new(width: usize) -> Frame {
+        Frame {
+            width,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        self.rows.get(y).and_then(|row| row.get(x))
+    }
+
+    /// Sets the cell, adding rows if `y` is past the last one.
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        assert!(x < self.width, "x is out of bounds");
+        while self.rows.len() <= y {
+            self.rows.push(vec![Cell::default(); self.width]);
+        }
+        self.rows[y][x] = cell;
+    }
+
+    /// The cells to update to turn `prev` into `self`, row by row.
+    ///
+    /// Cells missing from one of the frames count as blank.
+    pub fn diff(&self, prev: &Frame) -> Vec<CellUpdate> {
+        let width = self.width.max(prev.width);
+        let height = self.height().max(prev.height());
+        let mut updates = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let cell = self.get(x, y).copied().unwrap_or_default();
+                if prev.get(x, y).copied().unwrap_or_default() != cell {
+                    updates.push(CellUpdate { x, y, cell });
+                }
+            }
+        }
+        updates
+    }
+
+    /// Clears the screen and draws the whole frame.
+    pub fn ansi(&self) -> String {
+        let mut out = String::from("\x1b[H\x1b[2J");
+        out.push_str(&ansi_updates(&self.diff(&Frame::new(self.width))));
+        out
+    }
+
+    /// Draws the changes from `prev`, which is expected to be on the screen.
+    pub fn ansi_diff(&self, prev: &Frame) -> String {
+        ansi_updates(&self.diff(prev))
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows.iter().enumerate() {
+            if y > 0 {
+                f.write_char('\n')?;
+            }
+            let line: String = row.iter().map(|cell| cell.ch).collect();
+            f.write_str(line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the updates as escape sequences, only moving the cursor where the updates aren't
+/// contiguous and only switching styles when they change.
+pub fn ansi_updates(updates: &[CellUpdate]) -> String {
+    let mut out = String::new();
+    let mut cursor = None;
+    let mut style = None;
+    for update in updates {
+        if cursor != Some((update.x, update.y)) {
+            write!(out, "\x1b[{};{}H", update.y + 1, update.x + 1).unwrap();
+        }
+        if style != Some(update.cell.style) {
+            update.cell.style.write_sgr(&mut out);
+            style = Some(update.cell.style);
+        }
+        out.push(update.cell.ch);
+        cursor = Some((update.x + 1, update.y));
+    }
+    if style.is_some() && style != Some(CellStyle::default()) {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(lines: &[&str]) -> Frame {
+        let mut frame = Frame::new(5);
+        for (y, line) in lines.iter().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                frame.set(
+                    x,
+                    y,
+                    Cell {
+                        ch,
+                        style: CellStyle::default(),
+                    },
+                );
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn diffs_changed_cells_only() {
+        let prev = frame(&["abc", "de"]);
+        let curr = frame(&["abx", "de", "f"]);
+        let updates: Vec<_> = curr
+            .diff(&prev)
+            .into_iter()
+            .map(|update| (update.x, update.y, update.cell.ch))
+            .collect();
+        assert_eq!(updates, vec![(2, 0, 'x'), (0, 2, 'f')]);
+    }
+
+    #[test]
+    fn clears_removed_rows() {
+        let prev = frame(&["ab", "cd"]);
+        let curr = frame(&["ab"]);
+        assert_eq!(curr.ansi_diff(&prev), "\x1b[2;1H\x1b[0m  ");
+    }
+
+    #[test]
+    fn moves_cursor_for_gaps_only() {
+        let prev = frame(&["abcde"]);
+        let mut curr = frame(&["xycdz"]);
+        curr.set(
+            4,
+            0,
+            Cell {
+                ch: 'z',
+                style: CellStyle {
+                    bold: true,
+                    fg: Some(Color::Red),
+                    ..CellStyle::default()
+                },
+            },
+        );
+        assert_eq!(
+            curr.ansi_diff(&prev),
+            "\x1b[1;1H\x1b[0mxy\x1b[1;5H\x1b[0;1;31mz\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use crate::{
+    frame::{Cell, CellStyle, Color, Frame},
+    Error,
+};
+
+/// A node of a `LayoutTree`, handed out as the element of `NodeRef`s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+pub enum NodeKind {
+    Element(Element),
+    Text(String),
+    /// Takes up an index among its siblings, but isn't displayed.
+    Comment(String),
+}
+
+pub struct Element {
+    tag: String,
+    id: Option<String>,
+    style: ElementStyle,
+}
+
+impl Element {
+    fn new(tag: &str) -> Element {
+        Element {
+            tag: tag.to_owned(),
+            id: None,
+            style: ElementStyle::default(),
+        }
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn style(&self) -> &ElementStyle {
+        &self.style
+    }
+
+    fn is_block(&self) -> bool {
+        matches!(self.tag.as_str(), "div" | "p" | "ul" | "li")
+    }
+}
+
+/// The style properties of an element that can be displayed, inherited by its children.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ElementStyle {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+}
+
+impl ElementStyle {
+    /// Picks the supported properties: `color`, `background-color`, `font-weight`, `font-style`
+    /// and `text-decoration`; the others are ignored.
+    pub fn from_props<'a, I>(props: I) -> ElementStyle
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut style = ElementStyle::default();
+        for (prop, value) in props {
+            let value = value.trim();
+            match prop {
+                "color" => style.fg = Color::from_css(value),
+                "background-color" | "background" => style.bg = Color::from_css(value),
+                "font-weight" => style.bold = Some(value == "bold" || value == "bolder"),
+                "font-style" => style.italic = Some(value == "italic" || value == "oblique"),
+                "text-decoration" => style.underline = Some(value.contains("underline")),
+                _ => {}
+            }
+        }
+        style
+    }
+
+    /// The style of the cells of an element, with `parent` the style of its parent's cells.
+    fn apply(&self, parent: CellStyle) -> CellStyle {
+        CellStyle {
+            fg: self.fg.or(parent.fg),
+            bg: self.bg.or(parent.bg),
+            bold: self.bold.unwrap_or(parent.bold),
+            italic: self.italic.unwrap_or(parent.italic),
+            underline: self.underline.unwrap_or(parent.underline),
+        }
+    }
+}
+
+struct Slot {
+    kind: NodeKind,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+/// The nodes of a terminal app, mirroring the document of the web driver.
+///
+/// `div`, `p`, `ul` and `li` are laid out as blocks, `li` with a bullet, other tags are inline.
+/// `b`, `strong`, `i`, `em` and `u` style their text like the matching CSS properties would.
+pub struct LayoutTree {
+    slots: Vec<Option<Slot>>,
+    free: Vec<usize>,
+}
+
+impl Default for LayoutTree {
+    fn default() -> LayoutTree {
+        LayoutTree::new()
+    }
+}
+
+impl LayoutTree {
+    pub fn new() -> LayoutTree {
+        LayoutTree {
+            slots: vec![Some(Slot {
+                kind: NodeKind::Element(Element::new("div")),
+                parent: None,
+                children: Vec::new(),
+            })],
+            free: Vec::new(),
+        }
+    }
+
+    /// The block the app is mounted into.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn kind(&self, node: NodeId) -> Option<&NodeKind> {
+        self.slot(node).ok().map(|slot| &slot.kind)
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.slot(node).ok().and_then(|slot| slot.parent)
+    }
+
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        match self.slot(node) {
+            Ok(slot) => &slot.children,
+            Err(_) => &[],
+        }
+    }
+
+    /// The element with that `id` attribute.
+    ///
+    /// Detached elements are found too, e.g. the ones of a subtree that is being mounted.
+    pub fn find_by_id(&self, id: &str) -> Option<NodeId> {
+        let is_match = |slot: &Option<Slot>| {
+            match slot {
+                Some(Slot {
+                    kind: NodeKind::Element(elem),
+                    ..
+                }) => elem.id() == Some(id),
+                _ => false,
+            }
+        };
+        self.slots.iter().position(is_match).map(NodeId)
+    }
+
+    /// Lays out the nodes into a frame of that width.
+    pub fn render(&self, width: usize) -> Frame {
+        assert!(width > 0, "width must be at least 1");
+        let mut layouter = Layouter {
+            tree: self,
+            frame: Frame::new(width),
+            x: 0,
+            y: 0,
+            indent: 0,
+            is_line_empty: true,
+            has_space: false,
+        };
+        layouter.node(self.root(), CellStyle::default());
+        layouter.frame
+    }
+
+    /// Adds a detached node, to be inserted with `insert`.
+    pub(crate) fn create(&mut self, kind: NodeKind) -> NodeId {
+        let slot = Some(Slot {
+            kind,
+            parent: None,
+            children: Vec::new(),
+        });
+        match self.free.pop() {
+            Some(i) => {
+                self.slots[i] = slot;
+                NodeId(i)
+            }
+            None => {
+                self.slots.push(slot);
+                NodeId(self.slots.len() - 1)
+            }
+        }
+    }
+
+    pub(crate) fn create_element(&mut self, tag: &str) -> NodeId {
+        self.create(NodeKind::Element(Element::new(tag)))
+    }
+
+    /// Inserts `node` at `index` among the children of `parent`, appending it if `index` is past
+    /// the last child. The node is moved if it's already attached.
+    pub(crate) fn insert(
+        &mut self,
+        parent: NodeId,
+        index: usize,
+        node: NodeId,
+    ) -> Result<(), Error> {
+        self.detach(node)?;
+        let children = &mut self.slot_mut(parent)?.children;
+        let index = index.min(children.len());
+        children.insert(index, node);
+        self.slot_mut(node)?.parent = Some(parent);
+        Ok(())
+    }
+
+    pub(crate) fn append(&mut self, parent: NodeId, node: NodeId) -> Result<(), Error> {
+        self.detach(node)?;
+        self.slot_mut(parent)?.children.push(node);
+        self.slot_mut(node)?.parent = Some(parent);
+        Ok(())
+    }
+
+    /// Detaches the node and frees it along with its subtree.
+    pub(crate) fn remove(&mut self, node: NodeId) -> Result<(), Error> {
+        if node == self.root() {
+            Err("can't remove the root")?;
+        }
+        self.detach(node)?;
+        let mut nodes = vec![node];
+        while let Some(node) = nodes.pop() {
+            let slot = self.slots[node.0].take().ok_or("node is missing")?;
+            nodes.extend(slot.children);
+            self.free.push(node.0);
+        }
+        Ok(())
+    }
+
+    /// The index of `node` among the children of its parent.
+    pub(crate) fn index_of(&self, node: NodeId) -> Result<(NodeId, usize), Error> {
+        let parent = self.slot(node)?.parent.ok_or("node has no parent")?;
+        let index = self
+            .slot(parent)?
+            .children
+            .iter()
+            .position(|&child| child == node)
+            .ok_or("node is missing from its parent")?;
+        Ok((parent, index))
+    }
+
+    pub(crate) fn set_text(&mut self, node: NodeId, text: &str) -> Result<(), Error> {
+        match &mut self.slot_mut(node)?.kind {
+            NodeKind::Text(data) | NodeKind::Comment(data) => {
+                data.clear();
+                data.push_str(text);
+                Ok(())
+            }
+            NodeKind::Element(_) => Err("node is an element".into()),
+        }
+    }
+
+    pub(crate) fn element_mut(&mut self, node: NodeId) -> Result<&mut Element, Error> {
+        match &mut self.slot_mut(node)?.kind {
+            NodeKind::Element(elem) => Ok(elem),
+            _ => Err("node is not an element".into()),
+        }
+    }
+
+    /// Turns the element into another tag, keeping its children but resetting its attributes.
+    pub(crate) fn set_tag(&mut self, node: NodeId, tag: &str) -> Result<(), Error> {
+        *self.element_mut(node)? = Element::new(tag);
+        Ok(())
+    }
+
+    pub(crate) fn set_id(&mut self, node: NodeId, id: Option<&str>) -> Result<(), Error> {
+        self.element_mut(node)?.id = id.map(ToOwned::to_owned);
+        Ok(())
+    }
+
+    pub(crate) fn set_style(&mut self, node: NodeId, style: ElementStyle) -> Result<(), Error> {
+        self.element_mut(node)?.style = style;
+        Ok(())
+    }
+
+    fn detach(&mut self, node: NodeId) -> Result<(), Error> {
+        if let Some(parent) = self.slot_mut(node)?.parent.take() {
+            self.slot_mut(parent)?
+                .children
+                .retain(|&child| child != node);
+        }
+        Ok(())
+    }
+
+    fn slot(&self, node: NodeId) -> Result<&Slot, Error> {
+        match self.slots.get(node.0) {
+            Some(Some(slot)) => Ok(slot),
+            _ => Err("node is missing".into()),
+        }
+    }
+
+    fn slot_mut(&mut self, node: NodeId) -> Result<&mut Slot, Error> {
+        match self.slots.get_mut(node.0) {
+            Some(Some(slot)) => Ok(slot),
+            _ => Err("node is missing".into()),
+        }
+    }
+}
+
+/// Flows the text of the tree into lines, wrapping it at word boundaries.
+///
+/// Whitespace is collapsed like in HTML.
+struct Layouter<'a> {
+    tree: &'a LayoutTree,
+    frame: Frame,
+    x: usize,
+    y: usize,
+    /// The column the lines of the current block start at.
+    indent: usize,
+    is_line_empty: bool,
+    /// Whether a space is pending in between the last word and the next one.
+    has_space: bool,
+}
+
+impl<'a> Layouter<'a> {
+    fn node(&mut self, node: NodeId, style: CellStyle) {
+        let tree = self.tree;
+        let slot = match tree.slot(node) {
+            Ok(slot) => slot,
+            Err(_) => return,
+        };
+        match &slot.kind {
+            NodeKind::Element(elem) => self.element(elem, &slot.children, style),
+            NodeKind::Text(text) => self.text(text, style),
+            NodeKind::Comment(_) => {}
+        }
+    }
+
+    fn element(&mut self, elem: &Element, children: &[NodeId], parent_style: CellStyle) {
+        let mut style = elem.style.apply(parent_style);
+        match elem.tag() {
+            "b" | "strong" => style.bold = elem.style.bold.unwrap_or(true),
+            "i" | "em" => style.italic = elem.style.italic.unwrap_or(true),
+            "u" => style.underline = elem.style.underline.unwrap_or(true),
+            _ => {}
+        }
+        if !elem.is_block() {
+            for &child in children {
+                self.node(child, style);
+            }
+            return;
+        }
+
+        self.break_line();
+        let indent = self.indent;
+        if elem.tag() == "li" {
+            self.word("•", style);
+            self.x += 1;
+            self.indent = self.x;
+        }
+        for &child in children {
+            self.node(child, style);
+        }
+        self.indent = indent;
+        self.break_line();
+        if elem.tag() == "p" {
+            self.y += 1;
+        }
+    }
+
+    fn text(&mut self, text: &str, style: CellStyle) {
+        if text.starts_with(char::is_whitespace) {
+            self.has_space = true;
+        }
+        let mut has_words = false;
+        for word in text.split_whitespace() {
+            if self.has_space && !self.is_line_empty {
+                if self.x + 1 + word.chars().count() > self.frame.width() {
+                    self.new_line();
+                } else {
+                    self.put(' ', style);
+                }
+            }
+            self.word(word, style);
+            self.has_space = true;
+            has_words = true;
+        }
+        if has_words {
+            self.has_space = text.ends_with(char::is_whitespace);
+        }
+    }
+
+    /// Puts the word at the cursor, breaking it wherever it doesn't fit.
+    fn word(&mut self, word: &str, style: CellStyle) {
+        for ch in word.chars() {
+            if self.x >= self.frame.width() {
+                self.new_line();
+            }
+            self.put(ch, style);
+        }
+    }
+
+    fn put(&mut self, ch: char, style: CellStyle) {
+        self.frame.set(self.x, self.y, Cell { ch, style });
+        self.x += 1;
+        self.is_line_empty = false;
+    }
+
+    /// Starts a new line, unless the current one is still empty.
+    fn break_line(&mut self) {
+        if self.is_line_empty {
+            self.x = self.line_start();
+        } else {
+            self.new_line();
+        }
+        self.has_space = false;
+    }
+
+    fn new_line(&mut self) {
+        self.y += 1;
+        self.x = self.line_start();
+        self.is_line_empty = true;
+    }
+
+    /// Keeps at least one column for the content of deeply nested blocks.
+    fn line_start(&self) -> usize {
+        self.indent.min(self.frame.width() - 1)
+    }
+}
@@ -0,0 +1,25 @@
+#![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
+
+use futures::task::SpawnError;
+
+pub mod driver;
+pub mod frame;
+pub mod layout;
+
+#[derive(Debug)]
+pub enum Error {
+    Str(&'static str),
+    Spawn(SpawnError),
+}
+
+impl From<&'static str> for Error {
+    fn from(s: &'static str) -> Error {
+        Error::Str(s)
+    }
+}
+
+impl From<SpawnError> for Error {
+    fn from(err: SpawnError) -> Error {
+        Error::Spawn(err)
+    }
+}
@@ -1,19 +1,70 @@
 use crate::Error;
 use futures::{
     future::LocalFutureObj,
-    task::{LocalSpawn, SpawnError},
-    Future, FutureExt,
+    task::{LocalSpawn, LocalSpawnExt, SpawnError},
+    FutureExt,
+};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    rc::{Rc, Weak},
 };
 use vdom::{
     driver::{Driver, DriverCtx},
+    program::ProgramRoot,
     vdom::{
-        attr::{Attr, AttrDiffer, AttrRefValue, AttrVisitor},
-        node::{Comp, CompNode, Node, NodeDiffer, NodeVisitor, Tag, Text},
+        attr::{
+            Attr, AttrDiffer, AttrValue, AttrVisitor, ClassChange, ClassList, NodeRef, SignalAttr,
+            Style, StyleChange,
+        },
+        namespace::Namespace,
+        node::{
+            Comment, CommentStatic, Comp, CompNode, Node, NodeDiffer, NodeVisitor, Portal,
+            PortalTarget, RawHtml, SignalText, Suspense, Tag, Text,
+        },
+        signal::ReadSignal,
     },
 };
+use wasm_bindgen::JsCast;
 use web_sys as web;
 
-pub struct WebDriver {}
+#[derive(Default)]
+pub struct WebDriver {
+    templates: HashMap<&'static str, web::HtmlTemplateElement>,
+    /// Refreshes the dirty components of the app, set when the app is mounted.
+    refresh: Option<Rc<dyn Fn()>>,
+    is_refresh_spawned: bool,
+    error_handler: Option<Rc<dyn Fn(Error)>>,
+}
+
+impl WebDriver {
+    /// Handles the errors of spawned futures, instead of logging them to the console.
+    pub fn set_error_handler<F>(&mut self, f: F)
+    where
+        F: Fn(Error) + 'static,
+    {
+        self.error_handler = Some(Rc::new(f));
+    }
+
+    /// Returns the `<template>` element for the HTML, parsing it on first use.
+    fn template(&mut self, html: &'static str) -> Result<web::HtmlTemplateElement, Error> {
+        if let Some(template) = self.templates.get(html) {
+            return Ok(template.clone());
+        }
+        let template = web::window()
+            .ok_or("window is None")?
+            .document()
+            .ok_or("document is None")?
+            .create_element("template")?
+            .dyn_into::<web::HtmlTemplateElement>()
+            .map_err(|_| "template is not a HtmlTemplateElement")?;
+        template.set_inner_html(html);
+        self.templates.insert(html, template.clone());
+        Ok(template)
+    }
+}
 
 #[derive(Default)]
 pub struct AttrStore;
@@ -28,14 +79,35 @@ pub struct TextStore {
     text: Option<web::Text>,
 }
 
+#[derive(Default)]
+pub struct CommentStore {
+    comment: Option<web::Comment>,
+}
+
 #[derive(Default)]
 pub struct CompStore;
 
+#[derive(Default)]
+pub struct PortalStore {
+    /// Holds the children of the portal, appended to the target element.
+    container: Option<web::Element>,
+}
+
+#[derive(Default)]
+pub struct RawHtmlStore {
+    element: Option<web::Element>,
+}
+
 impl Driver for WebDriver {
     type AttrStore = AttrStore;
     type TagStore = TagStore;
     type TextStore = TextStore;
+    type CommentStore = CommentStore;
     type CompStore = CompStore;
+    type PortalStore = PortalStore;
+    type RawHtmlStore = RawHtmlStore;
+    type Element = web::Element;
+    type Err = Error;
 
     fn new_attr_store() -> AttrStore {
         Default::default()
@@ -49,17 +121,47 @@ impl Driver for WebDriver {
         Default::default()
     }
 
+    fn new_comment_store() -> CommentStore {
+        Default::default()
+    }
+
     fn new_comp_store() -> CompStore {
         Default::default()
     }
 
-    fn spawn<F>(&mut self, fut: F)
-    where
-        F: Future<Output = ()> + 'static,
-    {
-        wasm_bindgen_futures::spawn_local(futures::compat::Compat::new(Box::pin(
-            fut.map(|_| Ok(())),
-        )));
+    fn new_portal_store() -> PortalStore {
+        Default::default()
+    }
+
+    fn new_raw_html_store() -> RawHtmlStore {
+        Default::default()
+    }
+
+    fn request_render(&mut self) {
+        if self.is_refresh_spawned {
+            return;
+        }
+        if let Some(refresh) = self.refresh.clone() {
+            // retried on the next request if it can't be spawned
+            self.is_refresh_spawned = self
+                .spawn_local(futures::future::ready(()).map(move |_| refresh()))
+                .is_ok();
+        }
+    }
+
+    fn error_handler(&self) -> Rc<dyn Fn(Error)> {
+        match &self.error_handler {
+            Some(error_handler) => error_handler.clone(),
+            None => Rc::new(|err| web::console::error_1(&format!("{:?}", err).into())),
+        }
+    }
+}
+
+/// Spawns on the microtask queue of the browser.
+impl LocalSpawn for WebDriver {
+    fn spawn_local_obj(&mut self, future: LocalFutureObj<'static, ()>) -> Result<(), SpawnError> {
+        wasm_bindgen_futures::spawn_local(futures::compat::Compat::new(future.map(|_| Ok(()))));
+        Ok(())
     }
 }
 
@@ -67,49 +169,111 @@ pub struct App<N>
 where
     N: Node<WebDriver>,
 {
-    root_element: web::Element,
-    driver_ctx: DriverCtx<WebDriver>,
-    node: N,
+    state: Rc<RefCell<AppState<N>>>,
 }
 
 impl<N> App<N>
 where
-    N: Node<WebDriver>,
+    N: Node<WebDriver> + 'static,
 {
     pub fn new(mut node: N, root_element: web::Element) -> Result<App<N>, Error> {
-        let driver_ctx = DriverCtx::new(WebDriver {});
+        let driver_ctx = DriverCtx::new(WebDriver::default());
         node.visit(
             &mut 0,
             &mut NodeAddVisitor {
                 parent_element: &root_element,
+                namespace: namespace_of(&root_element),
                 driver_ctx: &driver_ctx,
             },
         )?;
-        Ok(App {
+        let state = Rc::new(RefCell::new(AppState {
             root_element,
-            driver_ctx,
+            driver_ctx: driver_ctx.clone(),
             node,
-        })
+        }));
+        let weak_state = Rc::downgrade(&state);
+        driver_ctx.with_mut(|drv| drv.refresh = Some(Rc::new(move || refresh(&weak_state))));
+        Ok(App { state })
     }
 
     pub fn set(&mut self, mut node: N) -> Result<(), Error> {
+        let state = &mut *self.state.borrow_mut();
         node.diff(
             &mut 0,
             &mut 0,
-            &mut self.node,
+            &mut state.node,
             &mut NodeStdDiffer {
-                driver_ctx: &self.driver_ctx,
-                parent_element: &self.root_element,
+                driver_ctx: &state.driver_ctx,
+                parent_element: &state.root_element,
+                namespace: namespace_of(&state.root_element),
             },
         )?;
-        self.node = node;
+        state.node = node;
         Ok(())
     }
+
+    pub fn driver_ctx(&self) -> DriverCtx<WebDriver> {
+        self.state.borrow().driver_ctx.clone()
+    }
+}
+
+/// Lets an `App` host a `Program`, e.g. `ProgramRunner::new(program, |view| App::new(view, elem))`.
+impl<N> ProgramRoot<WebDriver, N> for App<N>
+where
+    N: Node<WebDriver> + 'static,
+{
+    type Err = Error;
+
+    fn driver_ctx(&self) -> DriverCtx<WebDriver> {
+        App::driver_ctx(self)
+    }
+
+    fn set(&mut self, node: N) -> Result<(), Error> {
+        App::set(self, node)
+    }
+}
+
+struct AppState<N> {
+    root_element: web::Element,
+    driver_ctx: DriverCtx<WebDriver>,
+    node: N,
+}
+
+/// Renders the components that requested it again, if the app is still around.
+fn refresh<N>(state: &Weak<RefCell<AppState<N>>>)
+where
+    N: Node<WebDriver>,
+{
+    let state = match state.upgrade() {
+        Some(state) => state,
+        None => return,
+    };
+    let (driver_ctx, res) = {
+        let state = &mut *state.borrow_mut();
+        state
+            .driver_ctx
+            .with_mut(|drv| drv.is_refresh_spawned = false);
+        let res = state.node.visit(
+            &mut 0,
+            &mut NodeRefreshVisitor {
+                driver_ctx: &state.driver_ctx,
+                parent_element: &state.root_element,
+                namespace: namespace_of(&state.root_element),
+            },
+        );
+        (state.driver_ctx.clone(), res)
+    };
+    // reported after releasing the app, which the error handler may use
+    if let Err(err) = res {
+        driver_ctx.report_error(err);
+    }
 }
 
 struct NodeAddVisitor<'a> {
     driver_ctx: &'a DriverCtx<WebDriver>,
     parent_element: &'a web::Element,
+    /// The namespace of `parent_element`'s children.
+    namespace: Namespace,
 }
 
 impl<'a> NodeVisitor<WebDriver> for NodeAddVisitor<'a> {
@@ -119,18 +283,43 @@ impl<'a> NodeVisitor<WebDriver> for NodeAddVisitor<'a> {
     where
         T: Tag<WebDriver>,
     {
-        let elem = web::window()
-            .ok_or("window is None")?
-            .document()
-            .ok_or("document is None")?
-            .create_element(tag.tag())?;
-        tag.visit_attrs(&mut AttrAddVisitor {
-            parent_element: &elem,
-        })?;
-        tag.visit_children(&mut NodeAddVisitor {
-            driver_ctx: &self.driver_ctx,
-            parent_element: &elem,
-        })?;
+        let namespace = tag.namespace(self.namespace);
+        let elem = match tag.template() {
+            // falls back to creating the nodes one by one, if the parsed template doesn't match
+            // the tree, e.g. because the HTML parser reordered some elements
+            Some(html) if namespace == Namespace::Html => {
+                match self.instantiate_template(html, tag) {
+                    Ok(elem) => Some(elem),
+                    Err(err) => {
+                        // the macro skips the templates it knows to be rewritten, so this is a
+                        // case it misses
+                        if cfg!(debug_assertions) {
+                            let msg =
+                                format!("template `{}` doesn't match its tree: {:?}", html, err);
+                            web::console::warn_1(&msg.into());
+                        }
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        let elem = match elem {
+            Some(elem) => elem,
+            None => {
+                let elem = create_element(tag.tag(), namespace)?;
+                tag.visit_attrs(&mut AttrAddVisitor {
+                    driver_ctx: self.driver_ctx,
+                    parent_element: &elem,
+                })?;
+                tag.visit_children(&mut NodeAddVisitor {
+                    driver_ctx: self.driver_ctx,
+                    parent_element: &elem,
+                    namespace: namespace.of_children(tag.tag()),
+                })?;
+                elem
+            }
+        };
         let parent_node = AsRef::<web::Node>::as_ref(&self.parent_element);
         parent_node.insert_before(
             elem.as_ref(),
@@ -158,6 +347,24 @@ impl<'a> NodeVisitor<WebDriver> for NodeAddVisitor<'a> {
         Ok(())
     }
 
+    fn on_comment<C>(&mut self, index: usize, comment: &mut C) -> Result<(), Error>
+    where
+        C: Comment<WebDriver>,
+    {
+        let comment_node = web::window()
+            .ok_or("window is None")?
+            .document()
+            .ok_or("document is None")?
+            .create_comment(comment.get());
+        let parent_node = AsRef::<web::Node>::as_ref(&self.parent_element);
+        parent_node.insert_before(
+            comment_node.as_ref(),
+            parent_node.child_nodes().get(index as u32).as_ref(),
+        )?;
+        comment.driver_store().comment = Some(comment_node);
+        Ok(())
+    }
+
     fn on_comp<C>(
         &mut self,
         index: &mut usize,
@@ -169,204 +376,1138 @@ impl<'a> NodeVisitor<WebDriver> for NodeAddVisitor<'a> {
         comp.init_comp_ctx(self.driver_ctx.clone());
         comp.visit_rendered(index, self)
     }
-}
-
-struct NodeRemoveVisitor;
 
-impl NodeVisitor<WebDriver> for NodeRemoveVisitor {
-    type Err = Error;
-
-    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
+    fn on_portal<N>(&mut self, portal: &mut Portal<WebDriver, N>) -> Result<(), Error>
     where
-        T: Tag<WebDriver>,
+        N: Node<WebDriver>,
     {
-        let elem = tag
-            .driver_store()
-            .element
-            .as_ref()
-            .ok_or("element is None")?;
-        elem.remove();
+        let container = create_element("div", Namespace::Html)?;
+        portal.visit_children(&mut NodeAddVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: &container,
+            namespace: Namespace::Html,
+        })?;
+        target_element(portal.target())?.append_child(&container)?;
+        portal.driver_store().container = Some(container);
         Ok(())
     }
 
-    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Error>
+    fn on_raw_html(
+        &mut self,
+        index: usize,
+        raw_html: &mut RawHtml<WebDriver>,
+    ) -> Result<(), Error> {
+        let elem = create_raw_html_element(raw_html, self.namespace)?;
+        let parent_node = AsRef::<web::Node>::as_ref(&self.parent_element);
+        parent_node.insert_before(
+            elem.as_ref(),
+            parent_node.child_nodes().get(index as u32).as_ref(),
+        )?;
+        raw_html.driver_store().element = Some(elem);
+        Ok(())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        index: usize,
+        text: &mut SignalText<WebDriver, S>,
+    ) -> Result<(), Error>
     where
-        T: Text<WebDriver>,
+        S: ReadSignal,
+        S::Value: Display,
     {
-        let text_node = text.driver_store().text.as_ref().ok_or("text is None")?;
-        let node = AsRef::<web::Node>::as_ref(text_node);
-        node.parent_node()
-            .ok_or("text has no parent")?
-            .remove_child(node)?;
-        Ok(())
+        self.on_text(index, text)?;
+        bind_text(text)
     }
 
-    fn on_comp<C>(
+    fn on_suspense<N, Fb>(
         &mut self,
         index: &mut usize,
-        comp: &mut CompNode<WebDriver, C>,
-    ) -> Result<(), Self::Err>
+        suspense: &mut Suspense<WebDriver, N, Fb>,
+    ) -> Result<(), Error>
     where
-        C: Comp<WebDriver>,
+        N: Node<WebDriver> + 'static,
+        Fb: Node<WebDriver> + 'static,
     {
-        comp.visit_rendered(index, self)
+        spawn_suspense(self.driver_ctx, suspense)?;
+        suspense.visit_content(index, self)
     }
 }
 
-struct AttrAddVisitor<'a> {
-    parent_element: &'a web::Element,
+impl<'a> NodeAddVisitor<'a> {
+    /// Clones the cached template and binds the stores of the subtree to the cloned nodes.
+    fn instantiate_template<T>(
+        &self,
+        html: &'static str,
+        tag: &mut T,
+    ) -> Result<web::Element, Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        let template = self.driver_ctx.with_mut(|drv| drv.template(html))?;
+        let elem = template
+            .content()
+            .first_child()
+            .ok_or("template is empty")?
+            .clone_node_with_deep(true)?
+            .dyn_into::<web::Element>()
+            .map_err(|_| "template root is not an element")?;
+        if !elem.node_name().eq_ignore_ascii_case(tag.tag()) {
+            Err("template root doesn't match tag")?;
+        }
+        NodeBindVisitor::bind_children(&elem, tag)?;
+        Ok(elem)
+    }
 }
 
-impl<'a> AttrVisitor<WebDriver> for AttrAddVisitor<'a> {
-    type Err = Error;
+/// Fills in the driver stores of a subtree from already existing nodes.
+struct NodeBindVisitor<'a> {
+    parent_element: &'a web::Element,
+    count: usize,
+}
 
-    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Error>
+impl<'a> NodeBindVisitor<'a> {
+    fn bind_children<T>(elem: &web::Element, tag: &mut T) -> Result<(), Error>
     where
-        A: Attr<WebDriver>,
+        T: Tag<WebDriver>,
     {
-        if let Some(value) = attr_to_str(attr) {
-            self.parent_element.set_attribute(attr.name(), value)?;
+        let mut visitor = NodeBindVisitor {
+            parent_element: elem,
+            count: 0,
+        };
+        tag.visit_children(&mut visitor)?;
+        if visitor.count != elem.child_nodes().length() as usize {
+            Err("child count doesn't match")?;
         }
         Ok(())
     }
-}
 
-struct NodeStdDiffer<'a> {
-    driver_ctx: &'a DriverCtx<WebDriver>,
-    parent_element: &'a web::Element,
+    fn child(&mut self, index: usize) -> Result<web::Node, Error> {
+        self.count = index + 1;
+        let node = self
+            .parent_element
+            .child_nodes()
+            .get(index as u32)
+            .ok_or("child is missing")?;
+        Ok(node)
+    }
 }
 
-impl<'a> NodeDiffer<WebDriver> for NodeStdDiffer<'a> {
+impl<'a> NodeVisitor<WebDriver> for NodeBindVisitor<'a> {
     type Err = Error;
 
-    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Error>
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Error>
     where
-        N: Node<WebDriver>,
+        T: Tag<WebDriver>,
     {
-        curr.visit(
-            index,
-            &mut NodeAddVisitor {
-                driver_ctx: self.driver_ctx,
-                parent_element: &self.parent_element,
-            },
-        )
+        let elem = self
+            .child(index)?
+            .dyn_into::<web::Element>()
+            .map_err(|_| "child is not an element")?;
+        if !elem.node_name().eq_ignore_ascii_case(tag.tag()) {
+            Err("child doesn't match tag")?;
+        }
+        NodeBindVisitor::bind_children(&elem, tag)?;
+        tag.driver_store().element = Some(elem);
+        Ok(())
     }
 
-    fn on_node_removed<N>(
-        &mut self,
-        ancestor_index: &mut usize,
-        ancestor: &mut N,
-    ) -> Result<(), Error>
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), Error>
     where
-        N: Node<WebDriver>,
+        T: Text<WebDriver>,
     {
-        ancestor.visit(ancestor_index, &mut NodeRemoveVisitor)
+        let text_node = self
+            .child(index)?
+            .dyn_into::<web::Text>()
+            .map_err(|_| "child is not a text")?;
+        text.driver_store().text = Some(text_node);
+        Ok(())
     }
 
-    fn on_tag<T>(
-        &mut self,
-        _curr_index: usize,
-        _ancestor_index: usize,
-        curr: &mut T,
-        ancestor: &mut T,
-    ) -> Result<(), Error>
+    fn on_comment<C>(&mut self, index: usize, comment: &mut C) -> Result<(), Error>
     where
-        T: Tag<WebDriver>,
+        C: Comment<WebDriver>,
     {
-        let elem = ancestor
-            .driver_store()
-            .element
-            .take()
-            .ok_or("element is None")?;
-        curr.diff_attrs(
-            ancestor,
-            &mut AttrStdDiffer {
-                parent_element: &elem,
-            },
-        )?;
-        curr.diff_children(
-            ancestor,
-            &mut NodeStdDiffer {
-                driver_ctx: self.driver_ctx,
-                parent_element: &elem,
-            },
-        )?;
-        curr.driver_store().element = Some(elem);
+        let comment_node = self
+            .child(index)?
+            .dyn_into::<web::Comment>()
+            .map_err(|_| "child is not a comment")?;
+        comment.driver_store().comment = Some(comment_node);
         Ok(())
     }
 
-    fn on_text<T>(
+    fn on_comp<C>(
         &mut self,
-        _curr_index: usize,
-        _ancestor_index: usize,
-        curr: &mut T,
-        ancestor: &mut T,
+        _index: &mut usize,
+        _comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<WebDriver>,
+    {
+        Err("templates can't contain components".into())
+    }
+
+    fn on_portal<N>(&mut self, _portal: &mut Portal<WebDriver, N>) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        Err("templates can't contain portals".into())
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _index: usize,
+        _raw_html: &mut RawHtml<WebDriver>,
+    ) -> Result<(), Error> {
+        Err("templates can't contain raw HTML".into())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        _index: usize,
+        _text: &mut SignalText<WebDriver, S>,
     ) -> Result<(), Error>
     where
-        T: Text<WebDriver>,
+        S: ReadSignal,
+        S::Value: Display,
     {
-        let text = ancestor.driver_store().text.take().ok_or("text is None")?;
-        if curr.get() != ancestor.get() {
-            AsRef::<web::CharacterData>::as_ref(&text).set_data(curr.get());
-        }
-        curr.driver_store().text = Some(text);
-        Ok(())
+        Err("templates can't contain signals".into())
     }
 
-    fn on_comp<C>(
+    fn on_suspense<N, Fb>(
         &mut self,
-        curr_index: &mut usize,
-        ancestor_index: &mut usize,
-        curr: &mut CompNode<WebDriver, C>,
-        ancestor: &mut CompNode<WebDriver, C>,
-    ) -> Result<(), Self::Err>
+        _index: &mut usize,
+        _suspense: &mut Suspense<WebDriver, N, Fb>,
+    ) -> Result<(), Error>
     where
-        C: Comp<WebDriver>,
+        N: Node<WebDriver> + 'static,
+        Fb: Node<WebDriver> + 'static,
     {
-        if curr.comp_ctx().is_none() {
-            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
-            curr.set_comp_ctx(ctx.clone());
-        }
-        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+        Err("templates can't contain suspense".into())
     }
 }
 
-struct AttrStdDiffer<'a> {
+/// Looks for dirty components in a mounted tree, diffing their new render against the old one.
+struct NodeRefreshVisitor<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
     parent_element: &'a web::Element,
+    /// The namespace of `parent_element`'s children.
+    namespace: Namespace,
 }
 
-impl<'a> AttrDiffer<WebDriver> for AttrStdDiffer<'a> {
+impl<'a> NodeVisitor<WebDriver> for NodeRefreshVisitor<'a> {
     type Err = Error;
 
-    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Error>
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
     where
-        A: Attr<WebDriver>,
+        T: Tag<WebDriver>,
     {
-        match (attr_to_str(curr), attr_to_str(ancestor)) {
-            (Some(curr_val), Some(ancestor_val)) => {
-                if curr_val != ancestor_val {
-                    self.parent_element.set_attribute(curr.name(), curr_val)?;
-                }
-            }
-            (Some(curr_val), None) => {
-                self.parent_element.set_attribute(curr.name(), curr_val)?;
+        let elem = tag
+            .driver_store()
+            .element
+            .clone()
+            .ok_or("element is None")?;
+        let namespace = tag.namespace(self.namespace).of_children(tag.tag());
+        tag.visit_children(&mut NodeRefreshVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: &elem,
+            namespace,
+        })
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comment<C>(&mut self, _index: usize, _comment: &mut C) -> Result<(), Error>
+    where
+        C: Comment<WebDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<WebDriver>,
+    {
+        if comp.is_dirty() {
+            comp.refresh_rendered(
+                index,
+                &mut NodeStdDiffer {
+                    driver_ctx: self.driver_ctx,
+                    parent_element: self.parent_element,
+                    namespace: self.namespace,
+                },
+            )
+        } else {
+            comp.visit_rendered(index, self)
+        }
+    }
+
+    fn on_portal<N>(&mut self, portal: &mut Portal<WebDriver, N>) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        let container = portal
+            .driver_store()
+            .container
+            .clone()
+            .ok_or("container is None")?;
+        portal.visit_children(&mut NodeRefreshVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: &container,
+            namespace: Namespace::Html,
+        })
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _index: usize,
+        _raw_html: &mut RawHtml<WebDriver>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        _index: usize,
+        _text: &mut SignalText<WebDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        Ok(())
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<WebDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<WebDriver> + 'static,
+        Fb: Node<WebDriver> + 'static,
+    {
+        suspense.visit_content(index, self)
+    }
+}
+
+/// Removes the nodes from the document.
+///
+/// Walks the whole subtree to clear its `NodeRef`s, but only detaches its root nodes.
+struct NodeRemoveVisitor {
+    is_detached: bool,
+}
+
+impl NodeVisitor<WebDriver> for NodeRemoveVisitor {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        tag.visit_attrs(&mut NodeRefClearVisitor)?;
+        tag.visit_children(&mut NodeRemoveVisitor { is_detached: true })?;
+        if self.is_detached {
+            return Ok(());
+        }
+        let elem = tag
+            .driver_store()
+            .element
+            .as_ref()
+            .ok_or("element is None")?;
+        elem.remove();
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        if self.is_detached {
+            return Ok(());
+        }
+        let text_node = text.driver_store().text.as_ref().ok_or("text is None")?;
+        let node = AsRef::<web::Node>::as_ref(text_node);
+        node.parent_node()
+            .ok_or("text has no parent")?
+            .remove_child(node)?;
+        Ok(())
+    }
+
+    fn on_comment<C>(&mut self, _index: usize, comment: &mut C) -> Result<(), Error>
+    where
+        C: Comment<WebDriver>,
+    {
+        if self.is_detached {
+            return Ok(());
+        }
+        let comment_node = comment
+            .driver_store()
+            .comment
+            .as_ref()
+            .ok_or("comment is None")?;
+        let node = AsRef::<web::Node>::as_ref(comment_node);
+        node.parent_node()
+            .ok_or("comment has no parent")?
+            .remove_child(node)?;
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<WebDriver>,
+    {
+        comp.visit_rendered(index, self)
+    }
+
+    /// The container of a portal lives outside of the subtree, so it is always removed.
+    fn on_portal<N>(&mut self, portal: &mut Portal<WebDriver, N>) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        portal.visit_children(&mut NodeRemoveVisitor { is_detached: true })?;
+        let container = portal
+            .driver_store()
+            .container
+            .as_ref()
+            .ok_or("container is None")?;
+        container.remove();
+        Ok(())
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _index: usize,
+        raw_html: &mut RawHtml<WebDriver>,
+    ) -> Result<(), Error> {
+        if self.is_detached {
+            return Ok(());
+        }
+        let elem = raw_html
+            .driver_store()
+            .element
+            .as_ref()
+            .ok_or("element is None")?;
+        elem.remove();
+        Ok(())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        index: usize,
+        text: &mut SignalText<WebDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        // the binding is dropped along with the node
+        self.on_text(index, text)
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<WebDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<WebDriver> + 'static,
+        Fb: Node<WebDriver> + 'static,
+    {
+        suspense.visit_content(index, self)
+    }
+}
+
+struct NodeRefClearVisitor;
+
+impl AttrVisitor<WebDriver> for NodeRefClearVisitor {
+    type Err = Error;
+
+    fn on_attr<A>(&mut self, _attr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_style(&mut self, _style: &mut Style<WebDriver>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_class_list(&mut self, _class_list: &mut ClassList<WebDriver>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_node_ref(&mut self, node_ref: &mut NodeRef<WebDriver>) -> Result<(), Error> {
+        node_ref.set(None);
+        Ok(())
+    }
+
+    fn on_signal_attr<S>(&mut self, _attr: &mut SignalAttr<WebDriver, S>) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>,
+    {
+        Ok(())
+    }
+}
+
+struct AttrAddVisitor<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent_element: &'a web::Element,
+}
+
+impl<'a> AttrVisitor<WebDriver> for AttrAddVisitor<'a> {
+    type Err = Error;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        if let Some(value) = attr_to_str(attr) {
+            set_attribute(self.parent_element, attr, &value)?;
+        }
+        Ok(())
+    }
+
+    fn on_style(&mut self, style: &mut Style<WebDriver>) -> Result<(), Error> {
+        if style.is_empty() {
+            return Ok(());
+        }
+        match style_declaration(self.parent_element) {
+            Some(decl) => {
+                for (prop, value) in style.iter() {
+                    decl.set_property(prop, value)?;
+                }
+            }
+            None => {
+                self.parent_element
+                    .set_attribute("style", &style.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn on_class_list(&mut self, class_list: &mut ClassList<WebDriver>) -> Result<(), Error> {
+        let dom_class_list = self.parent_element.class_list();
+        for class in class_list.iter() {
+            dom_class_list.add_1(class)?;
+        }
+        Ok(())
+    }
+
+    fn on_node_ref(&mut self, node_ref: &mut NodeRef<WebDriver>) -> Result<(), Error> {
+        node_ref.set(Some(self.parent_element.clone()));
+        Ok(())
+    }
+
+    fn on_signal_attr<S>(&mut self, attr: &mut SignalAttr<WebDriver, S>) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>,
+    {
+        self.on_attr(attr)?;
+        bind_attr(self.driver_ctx, self.parent_element, attr);
+        Ok(())
+    }
+}
+
+struct NodeStdDiffer<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent_element: &'a web::Element,
+    /// The namespace of `parent_element`'s children.
+    namespace: Namespace,
+}
+
+impl<'a> NodeDiffer<WebDriver> for NodeStdDiffer<'a> {
+    type Err = Error;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        curr.visit(
+            index,
+            &mut NodeAddVisitor {
+                driver_ctx: self.driver_ctx,
+                parent_element: self.parent_element,
+                namespace: self.namespace,
+            },
+        )
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        ancestor.visit(
+            ancestor_index,
+            &mut NodeRemoveVisitor { is_detached: false },
+        )
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        let mut elem = ancestor
+            .driver_store()
+            .element
+            .take()
+            .ok_or("element is None")?;
+        let namespace = curr.namespace(self.namespace).of_children(curr.tag());
+        if curr.tag() == ancestor.tag() {
+            curr.diff_attrs(
+                ancestor,
+                &mut AttrStdDiffer {
+                    driver_ctx: self.driver_ctx,
+                    parent_element: &elem,
+                },
+            )?;
+        } else if namespace
+            == ancestor
+                .namespace(self.namespace)
+                .of_children(ancestor.tag())
+        {
+            elem = self.replace_element(&elem, curr, ancestor, true)?;
+        } else {
+            // the children can't be moved over, as elements keep the namespace they were created
+            // in, so they are mounted again
+            elem = self.replace_element(&elem, curr, ancestor, false)?;
+            ancestor.visit_children(&mut NodeRemoveVisitor { is_detached: true })?;
+            curr.visit_children(&mut NodeAddVisitor {
+                driver_ctx: self.driver_ctx,
+                parent_element: &elem,
+                namespace,
+            })?;
+            curr.driver_store().element = Some(elem);
+            return Ok(());
+        }
+        curr.diff_children(
+            ancestor,
+            &mut NodeStdDiffer {
+                driver_ctx: self.driver_ctx,
+                parent_element: &elem,
+                namespace,
+            },
+        )?;
+        curr.driver_store().element = Some(elem);
+        Ok(())
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        let text = ancestor.driver_store().text.take().ok_or("text is None")?;
+        if curr.get() != ancestor.get() {
+            AsRef::<web::CharacterData>::as_ref(&text).set_data(curr.get());
+        }
+        curr.driver_store().text = Some(text);
+        Ok(())
+    }
+
+    fn on_comment<C>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut C,
+        ancestor: &mut C,
+    ) -> Result<(), Error>
+    where
+        C: Comment<WebDriver>,
+    {
+        let comment = ancestor
+            .driver_store()
+            .comment
+            .take()
+            .ok_or("comment is None")?;
+        if curr.get() != ancestor.get() {
+            AsRef::<web::CharacterData>::as_ref(&comment).set_data(curr.get());
+        }
+        curr.driver_store().comment = Some(comment);
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<WebDriver, C>,
+        ancestor: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<WebDriver>,
+    {
+        if curr.comp_ctx().is_none() {
+            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
+            curr.set_comp_ctx(ctx.clone());
+        }
+        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+    }
+
+    fn on_portal<N>(
+        &mut self,
+        curr: &mut Portal<WebDriver, N>,
+        ancestor: &mut Portal<WebDriver, N>,
+    ) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        let container = ancestor
+            .driver_store()
+            .container
+            .take()
+            .ok_or("container is None")?;
+        if curr.target() != ancestor.target() {
+            // appending moves the container, along with all the children
+            target_element(curr.target())?.append_child(&container)?;
+        }
+        curr.diff_children(
+            ancestor,
+            &mut NodeStdDiffer {
+                driver_ctx: self.driver_ctx,
+                parent_element: &container,
+                namespace: Namespace::Html,
+            },
+        )?;
+        curr.driver_store().container = Some(container);
+        Ok(())
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut RawHtml<WebDriver>,
+        ancestor: &mut RawHtml<WebDriver>,
+    ) -> Result<(), Error> {
+        let mut elem = ancestor
+            .driver_store()
+            .element
+            .take()
+            .ok_or("element is None")?;
+        if curr.wrapper() != ancestor.wrapper() {
+            let new_elem = create_raw_html_element(curr, self.namespace)?;
+            AsRef::<web::Node>::as_ref(self.parent_element).replace_child(&new_elem, &elem)?;
+            elem = new_elem;
+        } else if curr.html() != ancestor.html() {
+            elem.set_inner_html(curr.html());
+        }
+        curr.driver_store().element = Some(elem);
+        Ok(())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut SignalText<WebDriver, S>,
+        ancestor: &mut SignalText<WebDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        let text = ancestor.driver_store().text.take().ok_or("text is None")?;
+        let is_bound = curr.adopt_binding(ancestor);
+        if !is_bound {
+            // the text of the ancestor may be outdated, the binding patched the node since
+            AsRef::<web::CharacterData>::as_ref(&text).set_data(curr.get());
+        }
+        curr.driver_store().text = Some(text);
+        if !is_bound {
+            bind_text(curr)?;
+        }
+        Ok(())
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut Suspense<WebDriver, N, Fb>,
+        ancestor: &mut Suspense<WebDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<WebDriver> + 'static,
+        Fb: Node<WebDriver> + 'static,
+    {
+        curr.diff_content(curr_index, ancestor_index, ancestor, self)?;
+        spawn_suspense(self.driver_ctx, curr)
+    }
+}
+
+impl<'a> NodeStdDiffer<'a> {
+    /// Replaces `elem` with a new element for the tag, moving the children over if
+    /// `move_children` is set.
+    fn replace_element<T>(
+        &self,
+        elem: &web::Element,
+        tag: &mut T,
+        ancestor: &mut T,
+        move_children: bool,
+    ) -> Result<web::Element, Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        // cleared before the new element is set, as both tags may share the same `NodeRef`
+        ancestor.visit_attrs(&mut NodeRefClearVisitor)?;
+        let new_elem = create_element(tag.tag(), tag.namespace(self.namespace))?;
+        tag.visit_attrs(&mut AttrAddVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: &new_elem,
+        })?;
+        if move_children {
+            while let Some(child) = elem.first_child() {
+                new_elem.append_child(&child)?;
             }
-            (None, Some(_)) => {
-                self.parent_element.remove_attribute(curr.name())?;
+        }
+        AsRef::<web::Node>::as_ref(self.parent_element).replace_child(&new_elem, elem)?;
+        Ok(new_elem)
+    }
+}
+
+struct AttrStdDiffer<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent_element: &'a web::Element,
+}
+
+impl<'a> AttrDiffer<WebDriver> for AttrStdDiffer<'a> {
+    type Err = Error;
+
+    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        if curr.value() == ancestor.value() {
+            return Ok(());
+        }
+        match attr_to_str(curr) {
+            Some(value) => set_attribute(self.parent_element, curr, &value)?,
+            None => remove_attribute(self.parent_element, curr)?,
+        }
+        Ok(())
+    }
+
+    fn on_attr_added<A>(&mut self, curr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        AttrAddVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: self.parent_element,
+        }
+        .on_attr(curr)
+    }
+
+    fn on_attr_removed<A>(&mut self, ancestor: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        if attr_to_str(ancestor).is_some() {
+            remove_attribute(self.parent_element, ancestor)?;
+        }
+        Ok(())
+    }
+
+    fn on_style(
+        &mut self,
+        curr: &mut Style<WebDriver>,
+        ancestor: &mut Style<WebDriver>,
+    ) -> Result<(), Error> {
+        match style_declaration(self.parent_element) {
+            Some(decl) => {
+                for change in curr.changes(ancestor) {
+                    match change {
+                        StyleChange::Set(prop, value) => decl.set_property(prop, value)?,
+                        StyleChange::Removed(prop) => {
+                            decl.remove_property(prop)?;
+                        }
+                    }
+                }
+            }
+            None => {
+                if curr.changes(ancestor).next().is_some() {
+                    self.parent_element
+                        .set_attribute("style", &curr.to_string())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn on_class_list(
+        &mut self,
+        curr: &mut ClassList<WebDriver>,
+        ancestor: &mut ClassList<WebDriver>,
+    ) -> Result<(), Error> {
+        let dom_class_list = self.parent_element.class_list();
+        for change in curr.changes(ancestor) {
+            match change {
+                ClassChange::Added(class) => dom_class_list.add_1(class)?,
+                ClassChange::Removed(class) => dom_class_list.remove_1(class)?,
             }
-            (None, None) => {}
         }
         Ok(())
     }
+
+    fn on_node_ref(
+        &mut self,
+        curr: &mut NodeRef<WebDriver>,
+        ancestor: &mut NodeRef<WebDriver>,
+    ) -> Result<(), Error> {
+        if !curr.ptr_eq(ancestor) {
+            ancestor.set(None);
+            curr.set(Some(self.parent_element.clone()));
+        }
+        Ok(())
+    }
+
+    fn on_signal_attr<S>(
+        &mut self,
+        curr: &mut SignalAttr<WebDriver, S>,
+        ancestor: &mut SignalAttr<WebDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>,
+    {
+        if curr.adopt_binding(ancestor) {
+            return Ok(());
+        }
+        // the value of the ancestor may be outdated, the binding patched the attribute since
+        match attr_to_str(curr) {
+            Some(value) => set_attribute(self.parent_element, curr, &value)?,
+            None => remove_attribute(self.parent_element, curr)?,
+        }
+        bind_attr(self.driver_ctx, self.parent_element, curr);
+        Ok(())
+    }
+}
+
+/// The inline style of HTML and SVG elements, other elements only have the `style` attribute.
+fn style_declaration(elem: &web::Element) -> Option<web::CssStyleDeclaration> {
+    match elem.dyn_ref::<web::HtmlElement>() {
+        Some(elem) => Some(elem.style()),
+        None => {
+            elem.dyn_ref::<web::SvgElement>()
+                .map(web::SvgElement::style)
+        }
+    }
 }
 
-fn attr_to_str<A>(attr: &A) -> Option<&str>
+fn attr_to_str<A>(attr: &A) -> Option<Cow<'_, str>>
 where
     A: Attr<WebDriver>,
 {
-    match attr.value() {
-        AttrRefValue::True => Some(attr.name()),
-        AttrRefValue::Null => None,
-        AttrRefValue::Str(s) => Some(s),
+    attr.value().to_str(attr.name())
+}
+
+/// The namespace of the children of an element that isn't managed by the driver.
+fn namespace_of(elem: &web::Element) -> Namespace {
+    let namespace = elem
+        .namespace_uri()
+        .and_then(|uri| Namespace::from_uri(&uri))
+        .unwrap_or_default();
+    namespace.of_children(&elem.local_name())
+}
+
+/// Spawns the future of the suspense, if it wasn't yet, to resolve it once done.
+fn spawn_suspense<N, Fb>(
+    driver_ctx: &DriverCtx<WebDriver>,
+    suspense: &mut Suspense<WebDriver, N, Fb>,
+) -> Result<(), Error>
+where
+    N: Node<WebDriver> + 'static,
+    Fb: Node<WebDriver> + 'static,
+{
+    if let Some(future) = suspense.take_future() {
+        let handle = suspense.handle();
+        let resolve_ctx = driver_ctx.clone();
+        driver_ctx.with_mut(|drv| {
+            drv.spawn_local(future.map(move |node| {
+                let res = handle.resolve(node, |anchor, fallback, node| {
+                    resolve_suspense(&resolve_ctx, anchor, fallback, node)
+                });
+                if let Some(Err(err)) = res {
+                    resolve_ctx.report_error(err);
+                }
+            }))
+        })?;
     }
+    Ok(())
+}
+
+/// Replaces the fallback of a suspense with the resolved node, right after the anchor.
+fn resolve_suspense<N, Fb>(
+    driver_ctx: &DriverCtx<WebDriver>,
+    anchor: &mut CommentStatic<WebDriver>,
+    fallback: &mut Fb,
+    node: &mut N,
+) -> Result<(), Error>
+where
+    N: Node<WebDriver>,
+    Fb: Node<WebDriver>,
+{
+    let anchor = anchor
+        .driver_store()
+        .comment
+        .as_ref()
+        .ok_or("comment is None")?;
+    let anchor = AsRef::<web::Node>::as_ref(anchor);
+    let parent_element = anchor
+        .parent_node()
+        .ok_or("anchor has no parent")?
+        .dyn_into::<web::Element>()
+        .map_err(|_| "anchor parent is not an element")?;
+    let child_nodes = parent_element.child_nodes();
+    let anchor_index = (0..child_nodes.length())
+        .position(|i| child_nodes.get(i).as_ref() == Some(anchor))
+        .ok_or("anchor is missing")?;
+    fallback.visit(
+        &mut (anchor_index + 1),
+        &mut NodeRemoveVisitor { is_detached: false },
+    )?;
+    node.visit(
+        &mut (anchor_index + 1),
+        &mut NodeAddVisitor {
+            driver_ctx,
+            parent_element: &parent_element,
+            namespace: namespace_of(&parent_element),
+        },
+    )
+}
+
+fn target_element(target: &PortalTarget<WebDriver>) -> Result<web::Element, Error> {
+    let elem = match target {
+        PortalTarget::Element(elem) => elem.clone(),
+        PortalTarget::Id(id) => {
+            web::window()
+                .ok_or("window is None")?
+                .document()
+                .ok_or("document is None")?
+                .get_element_by_id(id)
+                .ok_or("portal target not found")?
+        }
+    };
+    Ok(elem)
+}
+
+fn create_raw_html_element(
+    raw_html: &RawHtml<WebDriver>,
+    namespace: Namespace,
+) -> Result<web::Element, Error> {
+    let wrapper = raw_html.wrapper();
+    let elem = create_element(wrapper, Namespace::of_element(wrapper, namespace))?;
+    elem.set_inner_html(raw_html.html());
+    Ok(elem)
+}
+
+fn create_element(tag: &str, namespace: Namespace) -> Result<web::Element, Error> {
+    let document = web::window()
+        .ok_or("window is None")?
+        .document()
+        .ok_or("document is None")?;
+    let elem = match namespace {
+        Namespace::Html => document.create_element(tag)?,
+        namespace => document.create_element_ns(Some(namespace.uri()), tag)?,
+    };
+    Ok(elem)
+}
+
+fn set_attribute<A>(elem: &web::Element, attr: &A, value: &str) -> Result<(), Error>
+where
+    A: Attr<WebDriver>,
+{
+    patch_attribute(elem, attr.name(), attr.namespace(), Some(value))
+}
+
+fn remove_attribute<A>(elem: &web::Element, attr: &A) -> Result<(), Error>
+where
+    A: Attr<WebDriver>,
+{
+    patch_attribute(elem, attr.name(), attr.namespace(), None)
+}
+
+/// Sets the attribute, or removes it for `None`.
+fn patch_attribute(
+    elem: &web::Element,
+    name: &str,
+    namespace: Option<Namespace>,
+    value: Option<&str>,
+) -> Result<(), Error> {
+    match (namespace, value) {
+        (Some(namespace), Some(value)) => {
+            elem.set_attribute_ns(Some(namespace.uri()), name, value)?
+        }
+        (None, Some(value)) => elem.set_attribute(name, value)?,
+        (Some(namespace), None) => {
+            // namespaced attributes are removed by their name without the prefix
+            let local_name = name.splitn(2, ':').last().unwrap_or(name);
+            elem.remove_attribute_ns(Some(namespace.uri()), local_name)?
+        }
+        (None, None) => elem.remove_attribute(name)?,
+    }
+    Ok(())
+}
+
+/// Patches the mounted text node on every change of the signal.
+fn bind_text<S>(text: &mut SignalText<WebDriver, S>) -> Result<(), Error>
+where
+    S: ReadSignal,
+    S::Value: Display,
+{
+    let text_node = text.driver_store().text.clone().ok_or("text is None")?;
+    text.bind(move |value| AsRef::<web::CharacterData>::as_ref(&text_node).set_data(value));
+    Ok(())
+}
+
+/// Patches the attribute of `elem` on every change of the signal.
+fn bind_attr<S>(
+    driver_ctx: &DriverCtx<WebDriver>,
+    elem: &web::Element,
+    attr: &mut SignalAttr<WebDriver, S>,
+) where
+    S: ReadSignal,
+    S::Value: Clone + Into<AttrValue>,
+{
+    let driver_ctx = driver_ctx.clone();
+    let elem = elem.clone();
+    let name = attr.name().to_owned();
+    let namespace = attr.namespace();
+    attr.bind(move |value| {
+        if let Err(err) = patch_attribute(&elem, &name, namespace, value) {
+            driver_ctx.report_error(err);
+        }
+    });
 }
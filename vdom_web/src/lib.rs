@@ -1,5 +1,6 @@
 #![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
 
+use futures::task::SpawnError;
 use wasm_bindgen::JsValue;
 
 pub mod driver;
@@ -8,6 +9,7 @@ pub mod driver;
 pub enum Error {
     JsValue(JsValue),
     Str(&'static str),
+    Spawn(SpawnError),
 }
 
 impl From<JsValue> for Error {
@@ -21,3 +23,9 @@ impl From<&'static str> for Error {
         Error::Str(s)
     }
 }
+
+impl From<SpawnError> for Error {
+    fn from(err: SpawnError) -> Error {
+        Error::Spawn(err)
+    }
+}
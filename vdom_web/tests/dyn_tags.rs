@@ -0,0 +1,65 @@
+//! Runs in a browser, e.g. with `wasm-pack test --headless --firefox vdom_web`.
+#![cfg(target_arch = "wasm32")]
+
+use vdom::vdom::attr::NodeRef;
+use vdom::vdom::node::Node;
+use vdom_macro::html;
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn root_element() -> web_sys::Element {
+    web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("div")
+        .unwrap()
+}
+
+fn view(name: &'static str) -> impl Node<WebDriver> {
+    html! { div { (name) { g { circle r="1"; } } } }
+}
+
+fn child_namespace(root: &web_sys::Element) -> String {
+    let child = root.query_selector("g").unwrap().unwrap();
+    child.namespace_uri().unwrap()
+}
+
+#[wasm_bindgen_test]
+fn remounts_children_when_the_namespace_changes() {
+    let root = root_element();
+    let mut app = App::new(view("svg"), root.clone()).unwrap();
+    assert_eq!(child_namespace(&root), "http://www.w3.org/2000/svg");
+
+    app.set(view("div")).unwrap();
+    assert_eq!(child_namespace(&root), "http://www.w3.org/1999/xhtml");
+    assert_eq!(
+        root.inner_html(),
+        r#"<div><div><g><circle r="1"></circle></g></div></div>"#
+    );
+
+    app.set(view("svg")).unwrap();
+    assert_eq!(child_namespace(&root), "http://www.w3.org/2000/svg");
+}
+
+fn view_with_ref(name: &'static str, node_ref: &NodeRef<WebDriver>) -> impl Node<WebDriver> {
+    html! { div { (name) ref=(node_ref) { "a" } } }
+}
+
+#[wasm_bindgen_test]
+fn clears_node_refs_of_replaced_elements() {
+    let root = root_element();
+    let div_ref = NodeRef::new();
+    let span_ref = NodeRef::new();
+    let mut app = App::new(view_with_ref("div", &div_ref), root.clone()).unwrap();
+    assert_eq!(div_ref.get().unwrap().tag_name(), "DIV");
+
+    app.set(view_with_ref("span", &span_ref)).unwrap();
+    assert!(div_ref.get().is_none());
+    assert_eq!(span_ref.get().unwrap().tag_name(), "SPAN");
+
+    app.set(view_with_ref("p", &span_ref)).unwrap();
+    assert_eq!(span_ref.get().unwrap().tag_name(), "P");
+}
@@ -0,0 +1,51 @@
+//! Runs in a browser, e.g. with `wasm-pack test --headless --firefox vdom_web`.
+#![cfg(target_arch = "wasm32")]
+
+use vdom::vdom::node::Node;
+use vdom_macro::html;
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn root_element() -> web_sys::Element {
+    web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("div")
+        .unwrap()
+}
+
+/// The `ul` and the `table` are static, so they are instantiated from templates.
+fn view(show: bool) -> impl Node<WebDriver> {
+    let list = if show {
+        Some(html! { ul { li "a" li { b "b" } } })
+    } else {
+        None
+    };
+    let table = if show {
+        Some(html! { table { tbody { tr { td "c" } } } })
+    } else {
+        None
+    };
+    html! { div { (list) (table) } }
+}
+
+#[wasm_bindgen_test]
+fn binds_and_removes_templated_subtrees() {
+    let root = root_element();
+    let mut app = App::new(view(true), root.clone()).unwrap();
+    assert_eq!(
+        root.inner_html(),
+        "<div><ul><li>a</li><li><b>b</b></li></ul>\
+         <table><tbody><tr><td>c</td></tr></tbody></table></div>"
+    );
+
+    // removing the subtrees goes through the stores bound to the cloned nodes
+    app.set(view(false)).unwrap();
+    assert_eq!(root.inner_html(), "<div></div>");
+
+    app.set(view(true)).unwrap();
+    assert_eq!(root.first_element_child().unwrap().child_element_count(), 2);
+}
@@ -0,0 +1,578 @@
+use crate::{driver::XmlDriver, Error};
+use futures::{executor, future};
+use std::fmt::Display;
+use vdom::{
+    driver::DriverCtx,
+    vdom::{
+        attr::{Attr, AttrValue, AttrVisitor, ClassList, NodeRef, SignalAttr, Style},
+        namespace::Namespace,
+        node::{
+            Comment, Comp, CompNode, Node, NodeVisitor, Portal, RawHtml, SignalText, Suspense,
+            SuspenseCollector, Tag, Text,
+        },
+        signal::ReadSignal,
+    },
+};
+
+/// A node serialized as a well-formed XML document, e.g. an SVG image or a report.
+///
+/// Unlike HTML, every element without children is self-closing, whatever its tag. The namespace
+/// of SVG and MathML elements is declared on the outermost element switching to it, the same goes
+/// for `xmlns:xlink` on the elements with `xlink:` attributes. Explicit `xmlns` attributes are
+/// kept as is.
+///
+/// Components are rendered once, pending suspense is serialized with its fallback unless the
+/// document is resolved first. Portals and raw HTML aren't supported.
+pub struct Document<N> {
+    node: N,
+    driver_ctx: DriverCtx<XmlDriver>,
+    indent: Option<usize>,
+    declaration: bool,
+}
+
+impl<N> Document<N>
+where
+    N: Node<XmlDriver>,
+{
+    pub fn new(node: N) -> Document<N> {
+        Document::with_driver(node, XmlDriver::new())
+    }
+
+    pub fn with_driver(node: N, driver: XmlDriver) -> Document<N> {
+        Document {
+            node,
+            driver_ctx: DriverCtx::new(driver),
+            indent: None,
+            declaration: true,
+        }
+    }
+
+    /// Puts every element on its own line, indented by `width` spaces per level.
+    ///
+    /// Elements containing text are kept on one line, as whitespace would change their content.
+    pub fn pretty(mut self, width: usize) -> Document<N> {
+        self.indent = Some(width);
+        self
+    }
+
+    /// Leaves out the `<?xml ...?>` declaration.
+    pub fn without_declaration(mut self) -> Document<N> {
+        self.declaration = false;
+        self
+    }
+
+    pub fn driver_ctx(&self) -> DriverCtx<XmlDriver> {
+        self.driver_ctx.clone()
+    }
+
+    /// Blocks until all the suspense is resolved, including the one in the resolved content.
+    ///
+    /// Only the futures of the suspense are run meanwhile, not the ones spawned on the spawner of
+    /// the driver. Suspense waiting for those, e.g. for a component's stream, never resolves and
+    /// this never returns; such documents have to be serialized once the spawned work is done.
+    pub fn resolve(&mut self) {
+        loop {
+            let mut collector = SuspenseCollector::new(&self.driver_ctx);
+            // the collector never fails
+            let _ = self.node.visit(&mut 0, &mut collector);
+            let pending = collector.into_pending();
+            if pending.is_empty() {
+                return;
+            }
+            executor::block_on(future::join_all(pending));
+        }
+    }
+
+    /// Fails if the node isn't a single element, optionally surrounded by comments and
+    /// whitespace, or on names that aren't valid XML.
+    pub fn serialize(&mut self) -> Result<String, Error> {
+        let mut serializer = XmlSerializer {
+            driver_ctx: &self.driver_ctx,
+            indent: self.indent,
+            depth: 0,
+            namespace: Namespace::Html,
+            scope: Scope {
+                // elements in the HTML namespace are plain XML elements, without a declaration
+                default: Namespace::Html,
+                xlink: false,
+            },
+            children: Vec::new(),
+        };
+        self.node.visit(&mut 0, &mut serializer)?;
+
+        let mut out = String::new();
+        if self.declaration {
+            out.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        }
+        let mut elements = 0;
+        for child in serializer.children {
+            match child.kind {
+                MarkupKind::Element => elements += 1,
+                MarkupKind::Text if child.xml.trim().is_empty() => continue,
+                MarkupKind::Text => Err("text outside of the root element")?,
+                MarkupKind::Comment => {}
+            }
+            if self.indent.is_some() && !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&child.xml);
+        }
+        if elements != 1 {
+            Err("a document needs a single root element")?;
+        }
+        if self.indent.is_some() {
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MarkupKind {
+    Element,
+    Text,
+    Comment,
+}
+
+struct Markup {
+    kind: MarkupKind,
+    xml: String,
+}
+
+/// The namespaces declared by the ancestors.
+#[derive(Clone, Copy)]
+struct Scope {
+    default: Namespace,
+    xlink: bool,
+}
+
+/// Serializes the visited nodes, every one into its own markup.
+struct XmlSerializer<'a> {
+    driver_ctx: &'a DriverCtx<XmlDriver>,
+    indent: Option<usize>,
+    depth: usize,
+    /// The namespace of the parent's children.
+    namespace: Namespace,
+    scope: Scope,
+    children: Vec<Markup>,
+}
+
+impl<'a> XmlSerializer<'a> {
+    fn push(&mut self, kind: MarkupKind, xml: String) {
+        if !xml.is_empty() {
+            self.children.push(Markup { kind, xml });
+        }
+    }
+
+    /// Writes the children, each on its own line when pretty printing, unless there's text
+    /// among them.
+    fn write_children(&self, out: &mut String) {
+        let indent = match self.indent {
+            Some(width) if self.children.iter().all(|c| c.kind != MarkupKind::Text) => width,
+            _ => {
+                for child in &self.children {
+                    out.push_str(&child.xml);
+                }
+                return;
+            }
+        };
+        for child in &self.children {
+            out.push('\n');
+            push_indent(out, indent * self.depth);
+            out.push_str(&child.xml);
+        }
+        out.push('\n');
+        push_indent(out, indent * (self.depth - 1));
+    }
+}
+
+impl<'a> NodeVisitor<XmlDriver> for XmlSerializer<'a> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<XmlDriver>,
+    {
+        let name = tag.tag().to_owned();
+        if !is_name(&name) {
+            Err("invalid element name")?;
+        }
+        let namespace = tag.namespace(self.namespace);
+
+        let mut attrs = AttrCollector { attrs: Vec::new() };
+        tag.visit_attrs(&mut attrs)?;
+        let attrs = attrs.attrs;
+        for (i, (attr, _)) in attrs.iter().enumerate() {
+            if !is_name(attr) {
+                Err("invalid attribute name")?;
+            }
+            if attrs[..i].iter().any(|(prev, _)| prev == attr) {
+                Err("duplicate attribute")?;
+            }
+        }
+
+        let mut scope = self.scope;
+        let mut declarations = Vec::new();
+        match attrs.iter().find(|(attr, _)| attr == "xmlns") {
+            Some((_, uri)) => {
+                scope.default = Namespace::from_uri(uri).unwrap_or(Namespace::Html);
+            }
+            None if namespace != scope.default => {
+                declarations.push(("xmlns", namespace.uri()));
+                scope.default = namespace;
+            }
+            None => {}
+        }
+        if attrs.iter().any(|(attr, _)| attr == "xmlns:xlink") {
+            scope.xlink = true;
+        } else if !scope.xlink
+            && attrs
+                .iter()
+                .any(|(attr, _)| Namespace::of_attr(attr) == Some(Namespace::XLink))
+        {
+            declarations.push(("xmlns:xlink", Namespace::XLink.uri()));
+            scope.xlink = true;
+        }
+
+        let mut children = XmlSerializer {
+            driver_ctx: self.driver_ctx,
+            indent: self.indent,
+            depth: self.depth + 1,
+            namespace: namespace.of_children(&name),
+            scope,
+            children: Vec::new(),
+        };
+        tag.visit_children(&mut children)?;
+
+        let mut xml = format!("<{}", name);
+        let attrs = declarations
+            .into_iter()
+            .chain(attrs.iter().map(|(attr, value)| (&attr[..], &value[..])));
+        for (attr, value) in attrs {
+            xml.push(' ');
+            xml.push_str(attr);
+            xml.push_str("=\"");
+            escape_attr(value, &mut xml);
+            xml.push('"');
+        }
+        if children.children.is_empty() {
+            xml.push_str("/>");
+        } else {
+            xml.push('>');
+            children.write_children(&mut xml);
+            xml.push_str("</");
+            xml.push_str(&name);
+            xml.push('>');
+        }
+        self.push(MarkupKind::Element, xml);
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Error>
+    where
+        T: Text<XmlDriver>,
+    {
+        let mut xml = String::new();
+        escape_text(text.get(), &mut xml);
+        self.push(MarkupKind::Text, xml);
+        Ok(())
+    }
+
+    fn on_comment<C>(&mut self, _index: usize, comment: &mut C) -> Result<(), Error>
+    where
+        C: Comment<XmlDriver>,
+    {
+        let mut xml = String::from("<!--");
+        escape_comment(comment.get(), &mut xml);
+        xml.push_str("-->");
+        self.push(MarkupKind::Comment, xml);
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<XmlDriver, C>,
+    ) -> Result<(), Error>
+    where
+        C: Comp<XmlDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn on_portal<N>(&mut self, _portal: &mut Portal<XmlDriver, N>) -> Result<(), Error>
+    where
+        N: Node<XmlDriver>,
+    {
+        Err("portals aren't supported".into())
+    }
+
+    fn on_raw_html(
+        &mut self,
+        _index: usize,
+        _raw_html: &mut RawHtml<XmlDriver>,
+    ) -> Result<(), Error> {
+        Err("raw HTML isn't supported".into())
+    }
+
+    fn on_signal_text<S>(
+        &mut self,
+        index: usize,
+        text: &mut SignalText<XmlDriver, S>,
+    ) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Display,
+    {
+        self.on_text(index, text)
+    }
+
+    fn on_suspense<N, Fb>(
+        &mut self,
+        index: &mut usize,
+        suspense: &mut Suspense<XmlDriver, N, Fb>,
+    ) -> Result<(), Error>
+    where
+        N: Node<XmlDriver> + 'static,
+        Fb: Node<XmlDriver> + 'static,
+    {
+        suspense.visit_content(index, self)
+    }
+}
+
+/// Collects the attributes of a tag as they are serialized, leaving out the null ones.
+struct AttrCollector {
+    attrs: Vec<(String, String)>,
+}
+
+impl AttrVisitor<XmlDriver> for AttrCollector {
+    type Err = Error;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<XmlDriver>,
+    {
+        if let Some(value) = attr.value().to_str(attr.name()) {
+            self.attrs
+                .push((attr.name().to_owned(), value.into_owned()));
+        }
+        Ok(())
+    }
+
+    fn on_style(&mut self, style: &mut Style<XmlDriver>) -> Result<(), Error> {
+        let style = style.to_string();
+        if !style.is_empty() {
+            self.attrs.push(("style".to_owned(), style));
+        }
+        Ok(())
+    }
+
+    fn on_class_list(&mut self, class_list: &mut ClassList<XmlDriver>) -> Result<(), Error> {
+        let class = class_list.to_string();
+        if !class.is_empty() {
+            self.attrs.push(("class".to_owned(), class));
+        }
+        Ok(())
+    }
+
+    fn on_node_ref(&mut self, _node_ref: &mut NodeRef<XmlDriver>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_signal_attr<S>(&mut self, attr: &mut SignalAttr<XmlDriver, S>) -> Result<(), Error>
+    where
+        S: ReadSignal,
+        S::Value: Clone + Into<AttrValue>,
+    {
+        self.on_attr(attr)
+    }
+}
+
+fn push_indent(out: &mut String, width: usize) {
+    out.extend((0..width).map(|_| ' '));
+}
+
+/// Whether `name` is a valid XML name, prefixed or not.
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(ch) if ch.is_alphabetic() || ch == '_' || ch == ':' => {}
+        _ => return false,
+    }
+    chars.all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | ':' | '-' | '.'))
+}
+
+/// Whether the character may appear in an XML document at all, even as a reference.
+fn is_xml_char(ch: char) -> bool {
+    matches!(ch,
+        '\t' | '\n' | '\r' | '\u{20}'..='\u{d7ff}' | '\u{e000}'..='\u{fffd}' | '\u{10000}'..='\u{10ffff}'
+    )
+}
+
+/// Escapes the markup characters, the carriage return is escaped too as parsers would turn it
+/// into a line feed otherwise. Characters XML can't represent are left out.
+fn escape_text(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\r' => out.push_str("&#13;"),
+            ch if is_xml_char(ch) => out.push(ch),
+            _ => {}
+        }
+    }
+}
+
+/// Like `escape_text`, additionally escaping the quotes and the whitespace parsers would
+/// normalize into spaces.
+fn escape_attr(value: &str, out: &mut String) {
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("&quot;"),
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            ch => escape_text(ch.encode_utf8(&mut [0; 4]), out),
+        }
+    }
+}
+
+/// Comments can't be escaped, so `--` is split by a space, as is a trailing `-` which would
+/// run into the closing `-->`.
+fn escape_comment(comment: &str, out: &mut String) {
+    let mut prev = None;
+    for ch in comment.chars().filter(|&ch| is_xml_char(ch)) {
+        if ch == '-' && prev == Some('-') {
+            out.push(' ');
+        }
+        out.push(ch);
+        prev = Some(ch);
+    }
+    if prev == Some('-') {
+        out.push(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{channel::oneshot, FutureExt};
+    use vdom::vdom::node::{CommentStatic, TagDyn, TextDyn};
+    use vdom_macro::html;
+
+    #[test]
+    fn serializes_svg_with_namespaces() {
+        let mut document = Document::new(html! {
+            svg width="20" height="10" {
+                rect x="0" width="10" height="10" style:fill="red";
+                use xlink:href="#dot";
+                foreignObject { p "hi" }
+            }
+        });
+        assert_eq!(
+            document.serialize().unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">"#,
+                r#"<rect x="0" width="10" height="10" style="fill: red;"/>"#,
+                r##"<use xmlns:xlink="http://www.w3.org/1999/xlink" xlink:href="#dot"/>"##,
+                r#"<foreignObject><p xmlns="http://www.w3.org/1999/xhtml">hi</p></foreignObject>"#,
+                "</svg>",
+            )
+        );
+    }
+
+    #[test]
+    fn self_closes_empty_elements_only() {
+        // `br` is void in HTML, `div` isn't, neither matters in XML
+        let empty = String::new();
+        let mut document = Document::new(html! {
+            test-suite {
+                br;
+                div;
+                test-case { (TextDyn::new(empty)) }
+                test-case "ok"
+            }
+        })
+        .without_declaration();
+        assert_eq!(
+            document.serialize().unwrap(),
+            "<test-suite><br/><div/><test-case/><test-case>ok</test-case></test-suite>"
+        );
+    }
+
+    #[test]
+    fn escapes_text_attrs_and_comments() {
+        let text = "a < b && c > d\r\u{1}".to_owned();
+        let title = "\"quoted\"\n'single'".to_owned();
+        let mut document = Document::new(html! {
+            test-case title=(title) {
+                (TextDyn::new(text))
+                (CommentStatic::new("a -- b -"))
+            }
+        })
+        .without_declaration();
+        assert_eq!(
+            document.serialize().unwrap(),
+            concat!(
+                r#"<test-case title="&quot;quoted&quot;&#10;'single'">"#,
+                "a &lt; b &amp;&amp; c &gt; d&#13;<!--a - - b - -->",
+                "</test-case>",
+            )
+        );
+    }
+
+    #[test]
+    fn pretty_prints_elements_but_not_text() {
+        let mut document = Document::new(html! {
+            test-suite name="all" {
+                test-case { test-result "ok" }
+                test-case { "mixed " b "content" }
+            }
+        })
+        .pretty(2);
+        assert_eq!(
+            document.serialize().unwrap(),
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<test-suite name=\"all\">\n",
+                "  <test-case>\n",
+                "    <test-result>ok</test-result>\n",
+                "  </test-case>\n",
+                "  <test-case>mixed <b>content</b></test-case>\n",
+                "</test-suite>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        let mut document = Document::new(html! { test-case; test-case; });
+        assert!(document.serialize().is_err());
+
+        let mut document = Document::new(TagDyn::new("1st", (), ()));
+        assert!(document.serialize().is_err());
+    }
+
+    #[test]
+    fn resolves_suspense() {
+        let (sender, receiver) = oneshot::channel();
+        let content = receiver.map(|_| html! { test-result "ok" });
+        let suspense = Suspense::new(content, html! { test-result "pending" });
+        let mut document = Document::new(html! { test-case { (suspense) } }).without_declaration();
+        assert_eq!(
+            document.serialize().unwrap(),
+            "<test-case><!--suspense--><test-result>pending</test-result></test-case>"
+        );
+
+        sender.send(()).unwrap();
+        document.resolve();
+        assert_eq!(
+            document.serialize().unwrap(),
+            "<test-case><!--suspense--><test-result>ok</test-result></test-case>"
+        );
+    }
+}
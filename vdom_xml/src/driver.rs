@@ -0,0 +1,102 @@
+use crate::Error;
+use futures::{
+    future::LocalFutureObj,
+    task::{LocalSpawn, SpawnError},
+};
+use std::rc::Rc;
+use vdom::driver::Driver;
+
+/// Serializes nodes into XML documents, see `Document`.
+///
+/// Nothing is mounted, so the stores are empty and node refs never point to an element.
+pub struct XmlDriver {
+    spawner: Option<Box<dyn LocalSpawn>>,
+    error_handler: Option<Rc<dyn Fn(Error)>>,
+}
+
+impl XmlDriver {
+    /// A driver without a spawner, spawning fails with `SpawnError::shutdown`.
+    pub fn new() -> XmlDriver {
+        XmlDriver {
+            spawner: None,
+            error_handler: None,
+        }
+    }
+
+    /// Spawns the futures of the components, e.g. streams they subscribe to, with `spawner`.
+    pub fn with_spawner<S>(spawner: S) -> XmlDriver
+    where
+        S: LocalSpawn + 'static,
+    {
+        XmlDriver {
+            spawner: Some(Box::new(spawner)),
+            error_handler: None,
+        }
+    }
+
+    /// Handles the errors of spawned futures, instead of printing them to stderr.
+    pub fn set_error_handler<F>(&mut self, f: F)
+    where
+        F: Fn(Error) + 'static,
+    {
+        self.error_handler = Some(Rc::new(f));
+    }
+}
+
+impl Default for XmlDriver {
+    fn default() -> Self {
+        XmlDriver::new()
+    }
+}
+
+impl Driver for XmlDriver {
+    type AttrStore = ();
+    type TagStore = ();
+    type TextStore = ();
+    type CommentStore = ();
+    type CompStore = ();
+    type PortalStore = ();
+    type RawHtmlStore = ();
+    type Element = ();
+    type Err = Error;
+
+    fn new_attr_store() {}
+
+    fn new_tag_store() {}
+
+    fn new_text_store() {}
+
+    fn new_comment_store() {}
+
+    fn new_comp_store() {}
+
+    fn new_portal_store() {}
+
+    fn new_raw_html_store() {}
+
+    /// Documents are serialized on demand, so there is nothing to render.
+    fn request_render(&mut self) {}
+
+    fn error_handler(&self) -> Rc<dyn Fn(Error)> {
+        match &self.error_handler {
+            Some(error_handler) => error_handler.clone(),
+            None => Rc::new(|err| eprintln!("{:?}", err)),
+        }
+    }
+}
+
+impl LocalSpawn for XmlDriver {
+    fn spawn_local_obj(&mut self, future: LocalFutureObj<'static, ()>) -> Result<(), SpawnError> {
+        match &mut self.spawner {
+            Some(spawner) => spawner.spawn_local_obj(future),
+            None => Err(SpawnError::shutdown()),
+        }
+    }
+
+    fn status_local(&self) -> Result<(), SpawnError> {
+        match &self.spawner {
+            Some(spawner) => spawner.status_local(),
+            None => Err(SpawnError::shutdown()),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+#![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
+
+pub mod document;
+pub mod driver;
+
+#[derive(Debug)]
+pub enum Error {
+    Str(&'static str),
+}
+
+impl From<&'static str> for Error {
+    fn from(s: &'static str) -> Error {
+        Error::Str(s)
+    }
+}